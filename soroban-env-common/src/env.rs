@@ -161,11 +161,56 @@ macro_rules! call_macro_with_all_host_functions {
                 {"6", fn get_ledger_timestamp() -> Object }
                 /// Return the network identity of the current ledger as bytes.
                 {"7", fn get_ledger_network_id() -> Object }
+                /// Return a u32 in the range `[0, upper)` derived deterministically
+                /// from the current ledger and contract. Traps if `upper` is zero.
+                /// Not suitable where per-ledger predictability is a concern.
+                {"8", fn random_index(upper:RawVal) -> RawVal }
+                /// Return the timestamp number of the current ledger as an unboxed
+                /// u63 when it fits (which is always the case for real ledgers),
+                /// otherwise as a u64 object.
+                {"9", fn get_ledger_timestamp_small() -> RawVal }
+                /// Return the base reserve of the current ledger, in stroops, as a u32.
+                {"A", fn get_ledger_base_reserve() -> RawVal }
+                /// Return the base fee of the current ledger, in stroops, as a u32.
+                {"B", fn get_ledger_base_fee() -> RawVal }
+                /// Return a vector of the binary contractIDs of every contract on
+                /// the call stack, from the outermost to the running contract.
+                /// Host function frames are skipped.
+                {"C", fn get_call_stack() -> Object }
+                /// Compare two maps, or two vecs, and return a vec `[added, removed, changed]`
+                /// describing how `new` differs from `old`: `added` and `changed` are maps
+                /// from key to the new value, `removed` is a vec of keys. Vecs are treated
+                /// as maps keyed by u32 index. Traps on any other pair of values.
+                {"D", fn obj_diff(old:RawVal, new:RawVal) -> Object }
+                /// Apply a diff produced by `obj_diff` to `base`, returning the new map or vec.
+                /// Traps if the diff does not fit `base`, e.g. it removes a key `base` lacks.
+                {"E", fn obj_apply_diff(base:RawVal, diff:Object) -> RawVal }
+                /// Return true if the host is running in a read-only context, where
+                /// writing contract data and emitting contract events trap.
+                {"F", fn is_read_only() -> RawVal }
+                /// Return a vector of the arguments the running contract was called
+                /// with. Traps if the current frame is not a contract call.
+                {"G", fn get_current_call_args() -> Object }
+                /// Return the symbol naming the function the running contract was called
+                /// with, or void if that name is not a valid symbol. Traps if the current
+                /// frame is not a contract call.
+                {"H", fn get_current_call_func() -> RawVal }
+                /// Return true if `a` and `b` are structurally equal, as `obj_cmp` returning
+                /// 0 would say, but stop at the first difference. Maps and vecs of different
+                /// lengths are unequal without comparing any elements.
+                {"I", fn obj_eq(a:RawVal, b:RawVal) -> RawVal }
             }
 
             mod u64 "u" {
                 {"_", fn obj_from_u64(v:u64) -> Object }
                 {"0", fn obj_to_u64(obj:Object) -> u64 }
+                /// Add two objects containing u64s, returning an object containing the
+                /// sum. Traps on overflow.
+                {"1", fn u64_checked_add(a:Object, b:Object) -> Object }
+                /// Subtract `b` from `a`, both objects containing u64s. Traps on underflow.
+                {"2", fn u64_checked_sub(a:Object, b:Object) -> Object }
+                /// Multiply two objects containing u64s. Traps on overflow.
+                {"3", fn u64_checked_mul(a:Object, b:Object) -> Object }
             }
 
             /// Functions concerned with the i64 type
@@ -174,6 +219,13 @@ macro_rules! call_macro_with_all_host_functions {
                 {"_", fn obj_from_i64(v:i64) -> Object }
                 /// Convert an object containing an i64 to an i64.
                 {"0", fn obj_to_i64(obj:Object) -> i64 }
+                /// Add two objects containing i64s, returning an object containing the
+                /// sum. Traps on overflow.
+                {"1", fn i64_checked_add(a:Object, b:Object) -> Object }
+                /// Subtract `b` from `a`, both objects containing i64s. Traps on overflow.
+                {"2", fn i64_checked_sub(a:Object, b:Object) -> Object }
+                /// Multiply two objects containing i64s. Traps on overflow.
+                {"3", fn i64_checked_mul(a:Object, b:Object) -> Object }
             }
 
             mod map "m" {
@@ -208,6 +260,32 @@ macro_rules! call_macro_with_all_host_functions {
                 /// Return a new vector containing all the values in a map.
                 /// The new vector is ordered in the original map's key-sorted order.
                 {"A", fn map_values(m:Object) -> Object}
+                /// Return the number of keys present in both map `a` and map `b` as a u32.
+                {"B", fn map_key_intersection_count(a:Object, b:Object) -> RawVal}
+                /// Return a new map with every entry whose value equals `default` removed.
+                {"C", fn map_remove_default_values(m:Object, default:RawVal) -> Object}
+                /// Find the minimum value (not key) in a map, using the host's value ordering.
+                /// If the map is empty, return void.
+                {"D", fn map_min_value(m:Object) -> RawVal}
+                /// Find the maximum value (not key) in a map, using the host's value ordering.
+                /// If the map is empty, return void.
+                {"E", fn map_max_value(m:Object) -> RawVal}
+                /// Return the key whose value is the minimum in a map. On ties, the
+                /// first such key in key-sorted order. If the map is empty, return void.
+                {"F", fn map_argmin(m:Object) -> RawVal}
+                /// Return the key whose value is the maximum in a map. On ties, the
+                /// first such key in key-sorted order. If the map is empty, return void.
+                {"G", fn map_argmax(m:Object) -> RawVal}
+                /// Return the 32-byte sha256 hash of the XDR encoding of map `m`. Maps with equal
+                /// contents have equal checksums, however they were built.
+                {"H", fn map_checksum(m:Object) -> Object}
+                /// Return a new map holding only the entries of `m` whose key is the symbol
+                /// `sym`, or a vec whose first element is `sym`, as enum-like keys such as
+                /// `DataKey::Balance(id)` are encoded.
+                {"I", fn map_filter_keys_by_symbol(m:Object, sym:Symbol) -> Object}
+                /// Build a map from parallel vecs of keys and values in one step. Traps if the
+                /// vecs differ in length or `keys` holds a duplicate.
+                {"J", fn map_from_kv_vecs(keys:Object, vals:Object) -> Object}
             }
 
             mod vec "v" {
@@ -243,6 +321,21 @@ macro_rules! call_macro_with_all_host_functions {
                 /// Copy the elements from `start` index until `end` index, exclusive, in the vector and create a new vector from it.
                 /// Return the new vector. Traps if the index is out of bound.
                 {"A", fn vec_slice(v:Object, start:RawVal, end:RawVal) -> Object}
+                /// Return true if the U64 elements of vector `v` are strictly increasing by exactly one, i.e. form a
+                /// contiguous range with no gaps or duplicates. Empty and single-element vectors are contiguous.
+                /// Traps if any element is not a U64.
+                {"B", fn vec_is_contiguous_u64(v:Object) -> RawVal}
+                /// Return a new vector holding the elements of `v` in reverse order.
+                {"C", fn vec_reverse(v:Object) -> Object}
+                /// Return a new vector holding the elements of `v` in ascending order, using
+                /// the same ordering as `obj_cmp`. Equal elements keep their relative order.
+                {"D", fn vec_sort(v:Object) -> Object}
+                /// Return a new vector holding the elements of `v` with consecutive equal
+                /// elements collapsed into one. Only adjacent duplicates are removed, so call
+                /// `vec_sort` first to remove all of them.
+                {"E", fn vec_dedup(v:Object) -> Object}
+                /// Return a new vector holding each byte of binary `b` as a u32.
+                {"F", fn vec_from_binary(b:Object) -> Object}
             }
 
             mod ledger "l" {
@@ -254,6 +347,38 @@ macro_rules! call_macro_with_all_host_functions {
                 {"4", fn create_contract_from_contract(v: Object, salt: Object) -> Object}
                 {"5", fn create_token_from_ed25519(salt: Object, key: Object, sig: Object) -> Object}
                 {"6", fn create_token_from_contract(salt: Object) -> Object}
+                /// Delete every contract data entry of the running contract, including
+                /// its code and any expired entries, so it can no longer be called. Returns
+                /// the number of entries deleted as a u32. Only entries present in the
+                /// storage map (the footprint, when enforcing) are visible to this function.
+                {"7", fn destroy_current_contract() -> RawVal}
+                /// Return a vector of the keys of every unexpired contract data entry owned
+                /// by the running contract, in key order. As with `destroy_current_contract`,
+                /// only entries present in the storage map are visible.
+                {"8", fn get_contract_data_keys() -> Object}
+                /// Like `put_contract_data`, but the entry reads as absent once the ledger
+                /// sequence number exceeds the u32 `expire_seq`: `has_contract_data` returns
+                /// false and `get_contract_data` fails. A later put or delete of the key
+                /// clears the expiration.
+                {"9", fn put_contract_data_with_expiration(k:RawVal, v: RawVal, expire_seq: RawVal) -> RawVal}
+                /// Like `get_contract_data`, but returns void instead of trapping when the
+                /// key is absent, deleted or expired. Other storage faults still trap. A
+                /// stored void reads the same as an absent key; use `has_contract_data` to
+                /// tell them apart.
+                {"A", fn try_get_contract_data(k:RawVal) -> RawVal}
+                /// Return true if the contract with binary id `contract` is a native token
+                /// contract, false if it is a wasm contract. Traps if no such contract exists.
+                {"B", fn is_token_contract(contract:Object) -> RawVal}
+                /// Like `create_contract_from_contract`, but then calls `init_fn` on the new
+                /// contract with the vector `init_args`. If that call fails, the contract is
+                /// not created, even if the caller recovers from the failure.
+                {"C", fn create_and_init_contract(v: Object, salt: Object, init_fn: Symbol, init_args: Object) -> Object}
+                /// Return true if a contract with binary id `contract` has code deployed.
+                {"D", fn has_contract_code(contract:Object) -> RawVal}
+                /// Return the SHA-256 hash of the wasm code of the contract with binary id
+                /// `contract`. Traps if no such contract exists or it is a native token
+                /// contract, which has no wasm code.
+                {"E", fn get_contract_code_hash(contract:Object) -> Object}
             }
 
             mod call "d" {
@@ -322,6 +447,37 @@ macro_rules! call_macro_with_all_host_functions {
                 /// Outputs the BigInt's magnitude in the requested base in big-endian digit order into a binary array.
                 /// The sign is dropped. Radix must be in the range 2...256.
                 {"O", fn bigint_to_radix_be(x:Object, radix:RawVal) -> Object}
+                /// Performs the `*` operation with a u32 `scalar`, which is cheaper than `bigint_mul`
+                /// as it avoids constructing a second BigInt.
+                {"P", fn bigint_mul_u32(x:Object, scalar:RawVal) -> Object}
+                /// Returns, as a u32, the largest `bigint_bits` of all the BigInts in vector `v`, or 0 if `v` is empty.
+                /// Traps if any element is not a BigInt.
+                {"Q", fn bigint_vec_max_bits(v:Object) -> RawVal}
+                /// Constructs a BigInt from an i128, passed as a 16-byte big-endian two's-complement binary.
+                {"R", fn bigint_from_i128(x:Object) -> Object}
+                /// Converts a BigInt to an i128, returned as a 16-byte big-endian two's-complement binary.
+                /// Traps if the value cannot fit into i128.
+                {"S", fn bigint_to_i128(x:Object) -> Object}
+                /// Returns the sum of the BigInts in vector `v` modulo `modulus`, in the range
+                /// `[0, modulus)`. The running sum is reduced after each element. Traps if `modulus`
+                /// is not positive or any element is not a BigInt.
+                {"T", fn bigint_vec_sum_mod(v:Object, modulus:Object) -> Object}
+                /// Folds the BigInts in vector `v` left to right, starting from `init`, with the
+                /// operation named by the u32 `op`: 0 for add, 1 for mul, 2 for min, 3 for max, 4 for
+                /// and, 5 for or. Returns `init` if `v` is empty. Traps on an unknown `op` or if any
+                /// element is not a BigInt.
+                {"U", fn bigint_vec_fold(v:Object, init:Object, op:RawVal) -> Object}
+                /// Constructs an i128, as a 16-byte big-endian two's-complement binary, from its
+                /// low and high 64-bit halves.
+                {"V", fn obj_from_i128_pieces(lo:u64, hi:u64) -> Object}
+                /// Returns the low 64 bits of the i128 binary `obj`.
+                {"W", fn obj_to_i128_lo64(obj:Object) -> u64}
+                /// Returns the high 64 bits of the i128 binary `obj`.
+                {"X", fn obj_to_i128_hi64(obj:Object) -> u64}
+                /// Returns the sum of i128 binaries `a` and `b`. Traps on overflow.
+                {"Y", fn i128_add(a:Object, b:Object) -> Object}
+                /// Returns the product of i128 binaries `a` and `b`. Traps on overflow.
+                {"Z", fn i128_mul(a:Object, b:Object) -> Object}
             }
 
             mod binary "b" {
@@ -368,6 +524,36 @@ macro_rules! call_macro_with_all_host_functions {
                 /// Copies the elements from `start` index until `end` index, exclusive, in the binary and creates a new binary from it.
                 /// Returns the new binary. Traps if the index is out of bound.
                 {"F", fn binary_slice(b:Object, start:RawVal, end:RawVal) -> Object}
+                /// Returns a new binary holding the two's-complement negation of the big-endian number in `b`, at the
+                /// same width (the carry out of the top byte is discarded). Zero and the minimum value negate to themselves.
+                {"G", fn binary_twos_complement_neg(b:Object) -> Object}
+                /// Adds two equal-length big-endian binaries, returning a vector `[result, carry]` where `result` has the
+                /// same width as the inputs and `carry` is a bool. Traps if the lengths differ.
+                {"H", fn binary_add_be(a:Object, b:Object) -> Object}
+                /// Run-length encodes `b` as a new binary of `[byte, count]` pairs, where `count` is
+                /// 1 to 255. Longer runs are split across several pairs.
+                {"I", fn binary_rle_encode(b:Object) -> Object}
+                /// Decodes a binary produced by `binary_rle_encode`. Traps if its length is odd or
+                /// any count is zero.
+                {"J", fn binary_rle_decode(b:Object) -> Object}
+                /// Reads the big-endian u32 stored in the 4 bytes of binary `b` starting at `offset`.
+                /// Traps if they do not all lie within `b`.
+                {"K", fn binary_get_u32_be(b:Object, offset:RawVal) -> RawVal}
+                /// Returns a copy of binary `b` with the u32 `val` written big-endian into the 4 bytes
+                /// starting at `offset`. Traps if they do not all lie within `b`.
+                {"L", fn binary_set_u32_be(b:Object, offset:RawVal, val:RawVal) -> Object}
+                /// Returns true if binaries `a` and `b` hold the same bytes, taking the same time
+                /// wherever they differ. Binaries of different lengths are unequal.
+                {"M", fn binary_ct_eq(a:Object, b:Object) -> RawVal}
+                /// Returns a new binary whose bytes are the u32 elements of vector `v`. Traps if
+                /// any element is not a u32 in the range 0..=255. The inverse of `vec_from_binary`.
+                {"N", fn binary_from_vec(v:Object) -> Object}
+                /// Returns the length in bytes, as a u32, of the binary that `serialize_to_binary`
+                /// would return for `v`, without serializing it.
+                {"O", fn obj_serialized_size(v:RawVal) -> RawVal}
+                /// Like `binary_copy_from_linear_memory`, but traps instead of growing `b` if the
+                /// copy would extend past its end.
+                {"P", fn binary_copy_from_linear_memory_strict(b:Object, b_pos:RawVal, lm_pos:RawVal, len:RawVal) -> Object}
             }
 
             mod hash "h" {
@@ -383,6 +569,36 @@ macro_rules! call_macro_with_all_host_functions {
             mod crypto "c" {
                 {"_", fn compute_hash_sha256(x:Object) -> Object}
                 {"0", fn verify_sig_ed25519(x:Object, k:Object, s:Object) -> RawVal}
+                /// Verify signature `s` of message `x` under public key `k` using the
+                /// scheme named by the u32 `scheme`: 0 for ed25519 (32-byte key, 64-byte
+                /// signature), 1 for ECDSA over secp256k1 with SHA-256 (SEC1-encoded key,
+                /// 64-byte signature). Traps on an unknown scheme or failed verification.
+                {"1", fn verify_sig(scheme:RawVal, x:Object, k:Object, s:Object) -> RawVal}
+                /// Verify each ed25519 signature in the vector `sigs` of message `x`
+                /// under the public key at the same index in the vector `keys`. A void
+                /// signature counts as not signed. Returns true iff at least the u32
+                /// `threshold` signatures verify. Traps if `keys` and `sigs` differ in
+                /// length or hold malformed keys or signatures in any slot, including
+                /// slots after the threshold is met.
+                {"2", fn verify_threshold_ed25519(x:Object, keys:Object, sigs:Object, threshold:RawVal) -> RawVal}
+                /// Derive the 32-byte binary identifier of child `index` (a u32) of the
+                /// 32-byte binary identifier `parent`, as the sha256 hash of `parent`
+                /// followed by `index` in big-endian. Traps if `parent` is not 32 bytes.
+                {"3", fn derive_identifier(parent:Object, index:RawVal) -> Object}
+                /// Compute the keccak256 hash of the binary `x`, as a 32-byte binary.
+                {"4", fn compute_hash_keccak256(x:Object) -> Object}
+                /// Verify, as one batch, each ed25519 signature in the vector `sigs` of the
+                /// binary message at the same index in the vector `msgs` under the public key
+                /// at the same index in the vector `keys`. Returns true if every signature
+                /// verifies. Traps if the vectors differ in length, hold malformed keys or
+                /// signatures, or if any signature fails to verify.
+                {"5", fn verify_sig_ed25519_batch(msgs:Object, keys:Object, sigs:Object) -> RawVal}
+                /// Compute the HMAC-SHA256 of the binary `msg` under the binary `key`, as a
+                /// 32-byte binary.
+                {"6", fn compute_hmac_sha256(key:Object, msg:Object) -> Object}
+                /// Compute the sha256 hash of the concatenation of the binaries in the vector
+                /// `parts`, as a 32-byte binary, without building the concatenation.
+                {"7", fn compute_hash_sha256_parts(parts:Object) -> Object}
             }
 
             mod account "a" {
@@ -401,6 +617,14 @@ macro_rules! call_macro_with_all_host_functions {
                 /// master, and returns 0 if no such signer exists. Traps if no
                 /// such account exists.
                 {"2", fn account_get_signer_weight(a:Object, s:Object) -> RawVal}
+                /// Get the number of signers, not counting the master key, on
+                /// the account with ed25519 public key a (a is binary). Traps
+                /// if no such account exists.
+                {"3", fn account_get_num_signers(a:Object) -> RawVal}
+                /// Get the 32-byte key of the signer at index i on the account
+                /// with ed25519 public key a (a is binary), as binary. Traps if
+                /// no such account exists or if i is out of bounds.
+                {"4", fn account_get_signer_key_at(a:Object, i:RawVal) -> Object}
             }
         }
     };
@@ -1,9 +1,14 @@
 use std::{
     cell::{RefCell, RefMut},
+    collections::BTreeMap,
     rc::Rc,
 };
 
-use crate::{xdr::ScVmErrorCode, HostError};
+use crate::{
+    events::{DebugEvent, Events, HostEvent},
+    xdr::{ScStatusType, ScVmErrorCode},
+    HostError, Status,
+};
 
 // TODO: move this to an XDR enum
 #[repr(i32)]
@@ -72,8 +77,24 @@ pub enum CostType {
     BytesSlice = 57,
     BytesConcat = 58,
     CallArgsUnpack = 59,
+    // Comparisons of host objects, charged on the number of elements that may
+    // need comparing. BigInt comparisons are charged under `BigIntCmp`.
+    HostMapCmp = 60,
+    HostVecCmp = 61,
+    // Linear scan over an account's signers, charged on the number of signers.
+    AccountSignerScan = 62,
+    VerifySecp256k1Sig = 63,
+    ComputeKeccak256 = 64,
+    ComputeHmacSha256 = 65,
+    // Constant-time comparison of two binaries, charged on the longer length.
+    BytesCmp = 66,
 }
 
+/// The status for exceeding a per-[`CostType`] limit set with
+/// [`Budget::with_type_limit`]. Like running out of the overall budget, it is
+/// not recoverable.
+pub const TYPE_LIMIT_EXCEEDED: Status = Status::from_type_and_code(ScStatusType::VmError, 100);
+
 // TODO: add XDR support for iterating over all the elements of an enum
 impl CostType {
     pub fn variants() -> std::slice::Iter<'static, CostType> {
@@ -138,6 +159,13 @@ impl CostType {
             CostType::BytesSlice,
             CostType::BytesConcat,
             CostType::CallArgsUnpack,
+            CostType::HostMapCmp,
+            CostType::HostVecCmp,
+            CostType::AccountSignerScan,
+            CostType::VerifySecp256k1Sig,
+            CostType::ComputeKeccak256,
+            CostType::ComputeHmacSha256,
+            CostType::BytesCmp,
         ];
         VARIANTS.iter()
     }
@@ -317,11 +345,35 @@ pub(crate) struct BudgetImpl {
     /// Tracks the sums of _input_ values to the cost models, for purposes of
     /// calibration and reporting; not used for budget-limiting per se.
     inputs: Vec<u64>,
+    /// Tracks the number of times each cost type was charged, for reporting.
+    counts: Vec<u64>,
+    /// Optional per-[`CostType`] ceilings on the sum of inputs, enforced in
+    /// addition to the cpu and memory limits so that no single category can
+    /// consume the entire budget.
+    type_limits: Vec<Option<u64>>,
+    /// When set, charges are still accumulated but never fail. Only settable
+    /// through [`Budget::new_dry_run`], so it cannot be switched on for an
+    /// existing (production) budget, let alone from guest code.
+    dry_run: bool,
+    /// When set, charges are neither accumulated nor checked. Only set for
+    /// the duration of [`Host::with_budget_exemption`](crate::Host::with_budget_exemption).
+    exempt: bool,
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Budget(pub(crate) Rc<RefCell<BudgetImpl>>);
 
+/// A copy of a [`Budget`]'s consumption counters, taken by
+/// [`Budget::snapshot`] and reinstated by [`Budget::restore`]. Limits and
+/// cost models are not captured.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BudgetSnapshot {
+    cpu_insns_count: u64,
+    mem_bytes_count: u64,
+    inputs: Vec<u64>,
+    counts: Vec<u64>,
+}
+
 impl Budget {
     // Helper function to avoid multiple borrow_mut
     fn mut_budget<T, F>(&self, f: F) -> Result<T, HostError>
@@ -331,8 +383,74 @@ impl Budget {
         f(self.0.borrow_mut())
     }
 
+    /// Constructs a "dry run" budget, which records every charge exactly like
+    /// a normal budget but never returns an error for exceeding any limit.
+    /// This is meant for analysis tooling that wants to learn the full cost
+    /// of running a contract to completion; it must not be used to meter
+    /// real transactions.
+    pub fn new_dry_run() -> Self {
+        let budget = Budget::default();
+        budget.0.borrow_mut().dry_run = true;
+        budget
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.0.borrow().dry_run
+    }
+
+    // Suspends or resumes charging, returning whether it was suspended before.
+    pub(crate) fn set_exempt(&self, exempt: bool) -> bool {
+        std::mem::replace(&mut self.0.borrow_mut().exempt, exempt)
+    }
+
+    /// Sets the cpu instruction limit, in builder style.
+    pub fn with_cpu_limit(self, limit: u64) -> Self {
+        self.0.borrow_mut().cpu_insns.reset(limit);
+        self
+    }
+
+    /// Sets a ceiling on the sum of inputs charged to `ty`, in builder style.
+    /// Exceeding it fails [`Budget::charge`] even if the cpu and memory
+    /// limits still have room.
+    pub fn with_type_limit(self, ty: CostType, limit: u64) -> Self {
+        self.0.borrow_mut().type_limits[ty as usize] = Some(limit);
+        self
+    }
+
+    pub fn get_type_limit(&self, ty: CostType) -> Option<u64> {
+        self.0.borrow().type_limits[ty as usize]
+    }
+
     pub fn charge(&self, ty: CostType, input: u64) -> Result<(), HostError> {
+        if self.0.borrow().exempt {
+            return Ok(());
+        }
         self.get_input_mut(ty, |i| *i = i.saturating_add(input));
+        {
+            let count = &mut self.0.borrow_mut().counts[ty as usize];
+            *count = count.saturating_add(1);
+        }
+        if self.is_dry_run() {
+            return self.mut_budget(|mut b| {
+                // Errors here only signal that a limit was exceeded; the
+                // counts have been accumulated regardless.
+                let _ = b.cpu_insns.charge(ty, input);
+                let _ = b.mem_bytes.charge(ty, input);
+                Ok(())
+            });
+        }
+        if let Some(limit) = self.get_type_limit(ty) {
+            if self.get_input(ty) > limit {
+                // There is no host to record a debug event with here, so attach
+                // one directly to the error to identify which limit was hit.
+                let mut he: HostError = TYPE_LIMIT_EXCEEDED.into();
+                let de = DebugEvent::new()
+                    .msg("budget limit exceeded for cost type {}")
+                    .arg(ty);
+                he.events = Some(Events(vec![HostEvent::Debug(de)]));
+                return Err(he);
+            }
+        }
         self.mut_budget(|mut b| {
             b.cpu_insns.charge(ty, input)?;
             b.mem_bytes.charge(ty, input)
@@ -343,6 +461,16 @@ impl Budget {
         self.0.borrow().inputs[ty as usize]
     }
 
+    /// Returns, for every [`CostType`], the number of times it was charged and
+    /// the sum of the inputs it was charged with. This is a read-only view for
+    /// reporting and does not affect metering.
+    pub fn get_tracker(&self) -> BTreeMap<CostType, (u64, u64)> {
+        let b = self.0.borrow();
+        CostType::variants()
+            .map(|ty| (*ty, (b.counts[*ty as usize], b.inputs[*ty as usize])))
+            .collect()
+    }
+
     fn get_input_mut<F>(&self, ty: CostType, f: F)
     where
         F: FnOnce(&mut u64),
@@ -358,6 +486,31 @@ impl Budget {
         self.0.borrow().mem_bytes.get_count()
     }
 
+    /// Captures the current cpu, memory and per-[`CostType`] counters.
+    ///
+    /// This is intended for tooling such as fee estimation, which wants to run
+    /// an invocation, observe its cost and then undo it with
+    /// [`Budget::restore`]. It must never be exposed to guest code.
+    pub fn snapshot(&self) -> BudgetSnapshot {
+        let b = self.0.borrow();
+        BudgetSnapshot {
+            cpu_insns_count: b.cpu_insns.count,
+            mem_bytes_count: b.mem_bytes.count,
+            inputs: b.inputs.clone(),
+            counts: b.counts.clone(),
+        }
+    }
+
+    /// Restores the counters captured by [`Budget::snapshot`]. See the
+    /// caveats there: this is for tooling only, never for guest code.
+    pub fn restore(&self, snap: BudgetSnapshot) {
+        let mut b = self.0.borrow_mut();
+        b.cpu_insns.count = snap.cpu_insns_count;
+        b.mem_bytes.count = snap.mem_bytes_count;
+        b.inputs = snap.inputs;
+        b.counts = snap.counts;
+    }
+
     pub fn reset_unlimited(&self) {
         self.mut_budget(|mut b| {
             b.cpu_insns.reset(u64::MAX);
@@ -369,9 +522,13 @@ impl Budget {
     }
 
     pub fn reset_inputs(&self) {
-        for i in self.0.borrow_mut().inputs.iter_mut() {
+        let mut b = self.0.borrow_mut();
+        for i in b.inputs.iter_mut() {
             *i = 0;
         }
+        for c in b.counts.iter_mut() {
+            *c = 0;
+        }
     }
 
     #[cfg(test)]
@@ -403,10 +560,16 @@ impl Default for BudgetImpl {
             cpu_insns: Default::default(),
             mem_bytes: Default::default(),
             inputs: Default::default(),
+            counts: Default::default(),
+            type_limits: Default::default(),
+            dry_run: false,
+            exempt: false,
         };
 
         for _ct in CostType::variants() {
             b.inputs.push(0);
+            b.counts.push(0);
+            b.type_limits.push(None);
         }
 
         // For the time being we don't have "on chain" cost models
@@ -1,12 +1,19 @@
 use std::fmt::Display;
+use std::io::Write;
 
-use crate::{xdr, xdr::ContractEvent, RawVal, Status};
+use crate::{
+    budget::CostType,
+    xdr,
+    xdr::{ContractEvent, Hash, WriteXdr},
+    RawVal, Status,
+};
 #[cfg(feature = "vm")]
 use crate::{
     xdr::{ScUnknownErrorCode, ScVmErrorCode},
     HostError,
 };
 use log::debug;
+use sha2::{Digest, Sha256};
 use tinyvec::TinyVec;
 
 // TODO: optimize storage on this to use pools / bumpalo / etc.
@@ -39,6 +46,78 @@ impl Events {
         self.0.push(HostEvent::Contract(ce))
     }
 
+    // Drops any contract events recorded at or after index `len`, keeping all
+    // debug events so that diagnostics from a failed sub-transaction survive.
+    pub(crate) fn rollback(&mut self, len: usize) {
+        let mut i = 0;
+        self.0.retain(|e| {
+            let keep = i < len || matches!(e, HostEvent::Debug(_));
+            i += 1;
+            keep
+        });
+    }
+
+    fn contract_events(&self) -> impl Iterator<Item = &ContractEvent> {
+        self.0.iter().filter_map(|e| match e {
+            HostEvent::Contract(ce) => Some(ce),
+            HostEvent::Debug(_) => None,
+        })
+    }
+
+    /// Writes the contract events, skipping debug events, to `w` as the XDR
+    /// of a `ContractEvent` array. Events are encoded one at a time, so the
+    /// whole sequence is never buffered.
+    pub fn write_contract_events_xdr<W: Write>(&self, w: &mut W) -> Result<(), xdr::Error> {
+        let count = u32::try_from(self.contract_events().count())
+            .map_err(|_| xdr::Error::LengthExceedsMax)?;
+        count.write_xdr(w)?;
+        for ce in self.contract_events() {
+            ce.write_xdr(w)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the contract events in the order they were recorded, with
+    /// debug events stripped. This is the sequence committed to by
+    /// [`Events::contract_events_root`].
+    pub fn canonical_contract_events(&self) -> Vec<ContractEvent> {
+        self.contract_events().cloned().collect()
+    }
+
+    /// Returns the root of a binary Merkle tree over the XDR encodings of
+    /// [`Events::canonical_contract_events`]. Leaves are hashed as
+    /// `sha256(0x00 || event)` and inner nodes as `sha256(0x01 || left ||
+    /// right)`; a node without a sibling moves up a level unchanged. With no
+    /// contract events the root is `sha256("")`.
+    pub fn contract_events_root(&self) -> Result<Hash, xdr::Error> {
+        let mut level = self
+            .contract_events()
+            .map(|ce| {
+                let mut buf = vec![0u8];
+                ce.write_xdr(&mut buf)?;
+                Ok(Sha256::digest(&buf).into())
+            })
+            .collect::<Result<Vec<[u8; 32]>, xdr::Error>>()?;
+        if level.is_empty() {
+            return Ok(Hash(Sha256::digest(b"").into()));
+        }
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [l, r] => Sha256::new()
+                        .chain_update([1u8])
+                        .chain_update(l)
+                        .chain_update(r)
+                        .finalize()
+                        .into(),
+                    _ => pair[0],
+                })
+                .collect();
+        }
+        Ok(Hash(level[0]))
+    }
+
     pub fn dump_to_debug_log(&self) {
         for e in self.0.iter() {
             match e {
@@ -53,6 +132,7 @@ impl Events {
 pub enum DebugArg {
     Str(&'static str),
     Val(RawVal),
+    CostType(CostType),
 }
 
 impl From<RawVal> for DebugArg {
@@ -67,6 +147,12 @@ impl From<&'static str> for DebugArg {
     }
 }
 
+impl From<CostType> for DebugArg {
+    fn from(ty: CostType) -> Self {
+        DebugArg::CostType(ty)
+    }
+}
+
 impl Default for DebugArg {
     fn default() -> Self {
         DebugArg::Str("")
@@ -78,6 +164,7 @@ impl Display for DebugArg {
         match self {
             DebugArg::Str(s) => write!(f, "{}", s),
             DebugArg::Val(rv) => write!(f, "{:?}", rv),
+            DebugArg::CostType(ty) => write!(f, "{:?}", ty),
         }
     }
 }
@@ -6,6 +6,7 @@ use core::cmp::Ordering;
 use core::fmt::Debug;
 use im_rc::{OrdMap, Vector};
 use num_bigint::Sign;
+use sha2::{Digest, Sha256};
 use soroban_env_common::{EnvVal, TryConvert, TryFromVal, TryIntoVal, OK, UNKNOWN_ERROR};
 
 use soroban_env_common::xdr::{
@@ -14,15 +15,16 @@ use soroban_env_common::xdr::{
 };
 
 use crate::budget::{Budget, CostType};
-use crate::events::{DebugError, DebugEvent, Events};
-use crate::storage::Storage;
+use crate::events::{DebugError, DebugEvent, Events, HostEvent};
+use crate::storage::{Storage, StorageDiffKind};
 use crate::weak_host::WeakHost;
 
 use crate::xdr;
 use crate::xdr::{
     ContractDataEntry, HostFunction, LedgerEntry, LedgerEntryData, LedgerEntryExt, LedgerKey,
-    ScBigInt, ScContractCode, ScHostContextErrorCode, ScHostFnErrorCode, ScHostObjErrorCode,
-    ScHostStorageErrorCode, ScHostValErrorCode, ScMap, ScMapEntry, ScObject, ScVal, ScVec,
+    LedgerKeyContractData, ScBigInt, ScContractCode, ScHostContextErrorCode, ScHostFnErrorCode,
+    ScHostObjErrorCode, ScHostStorageErrorCode, ScHostValErrorCode, ScMap, ScMapEntry, ScObject,
+    ScObjectType, ScStatusType, ScVal, ScVec, ScVmErrorCode,
 };
 use std::rc::Rc;
 
@@ -32,10 +34,11 @@ use crate::CheckedEnv;
 use crate::SymbolStr;
 #[cfg(feature = "vm")]
 use crate::Vm;
-use crate::{EnvBase, IntoVal, Object, RawVal, RawValConvertible, Symbol, Val};
+use crate::{EnvBase, IntoVal, Object, RawVal, RawValConvertible, Status, Symbol, Val};
 
 mod conversion;
 mod data_helper;
+mod diff;
 mod err_helper;
 mod error;
 pub(crate) mod metered_bigint;
@@ -43,6 +46,7 @@ pub(crate) mod metered_clone;
 pub(crate) mod metered_map;
 pub(crate) mod metered_vector;
 mod validity;
+pub(crate) mod xdr_size;
 pub use error::HostError;
 
 use self::metered_bigint::MeteredBigInt;
@@ -50,13 +54,15 @@ use self::metered_clone::MeteredClone;
 use self::metered_map::MeteredOrdMap;
 use self::metered_vector::MeteredVector;
 
-/// Saves host state (storage and objects) for rolling back a (sub-)transaction
-/// on error. A helper type used by [`FrameGuard`].
+/// Saves host state (storage, objects and events) for rolling back a
+/// (sub-)transaction on error. A helper type used by [`FrameGuard`].
 // Notes on metering: `RollbackPoint` are metered under Frame operations
 #[derive(Clone)]
 pub(crate) struct RollbackPoint {
     storage: MeteredOrdMap<LedgerKey, Option<LedgerEntry>>,
+    expirations: OrdMap<LedgerKey, u32>,
     objects: usize,
+    events: usize,
 }
 
 #[cfg(feature = "testutils")]
@@ -85,6 +91,56 @@ pub(crate) enum Frame {
     TestContract(Hash),
 }
 
+impl Frame {
+    /// The ID of the contract running in this frame, if any.
+    fn contract_id(&self) -> Option<&Hash> {
+        match self {
+            #[cfg(feature = "vm")]
+            Frame::ContractVM(vm) => Some(&vm.contract_id),
+            Frame::HostFunction(_) => None,
+            Frame::Token(id) => Some(id),
+            #[cfg(feature = "testutils")]
+            Frame::TestContract(id) => Some(id),
+        }
+    }
+}
+
+/// What kind of frame is on top of the context stack, and the contract it
+/// runs, as returned by [`Host::current_frame_info`]. Unlike the frame itself
+/// this holds no reference into the host.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FrameInfo {
+    #[cfg(feature = "vm")]
+    ContractVM(Hash),
+    HostFunction(HostFunction),
+    Token(Hash),
+    #[cfg(feature = "testutils")]
+    TestContract(Hash),
+}
+
+impl From<&Frame> for FrameInfo {
+    fn from(frame: &Frame) -> Self {
+        match frame {
+            #[cfg(feature = "vm")]
+            Frame::ContractVM(vm) => FrameInfo::ContractVM(vm.contract_id.clone()),
+            Frame::HostFunction(hf) => FrameInfo::HostFunction(*hf),
+            Frame::Token(id) => FrameInfo::Token(id.clone()),
+            #[cfg(feature = "testutils")]
+            Frame::TestContract(id) => FrameInfo::TestContract(id.clone()),
+        }
+    }
+}
+
+/// A contract call in progress, captured when its frame is pushed.
+#[derive(Clone)]
+pub(crate) struct CallInfo {
+    // The depth of the context stack at which the call's frame sits.
+    depth: usize,
+    // The called function, if its name is a valid [`Symbol`].
+    func: Option<Symbol>,
+    args: HostVec,
+}
+
 /// Temporary helper for denoting a slice of guest memory, as formed by
 /// various binary operations.
 #[cfg(feature = "vm")]
@@ -94,12 +150,76 @@ struct VmSlice {
     len: u32,
 }
 
+/// Signature scheme tags accepted by the `verify_sig` host function.
+pub const SIG_SCHEME_ED25519: u32 = 0;
+pub const SIG_SCHEME_SECP256K1: u32 = 1;
+
+/// Operation tags accepted by the `bigint_vec_fold` host function.
+pub const BIGINT_FOLD_ADD: u32 = 0;
+pub const BIGINT_FOLD_MUL: u32 = 1;
+pub const BIGINT_FOLD_MIN: u32 = 2;
+pub const BIGINT_FOLD_MAX: u32 = 3;
+pub const BIGINT_FOLD_AND: u32 = 4;
+pub const BIGINT_FOLD_OR: u32 = 5;
+
+/// The maximum depth of the context stack, unless overridden with
+/// [`Host::set_max_frame_depth`].
+pub const DEFAULT_MAX_FRAME_DEPTH: usize = 100;
+
+/// The maximum number of host objects, unless overridden with
+/// [`Host::set_max_objects`].
+pub const DEFAULT_MAX_OBJECTS: usize = 100_000;
+
+// Statuses the host defines for conditions XDR has no code for. Their codes
+// are numbered from 100 within each status type, clear of the codes XDR
+// defines, so none collides with a status reported for any other reason. They
+// print as `UnknownCode` and cannot be converted to an `ScStatus`. The budget's
+// `TYPE_LIMIT_EXCEEDED` is one of them.
+
+/// The status for recording a contract event beyond the cap set with
+/// [`Host::set_max_events_per_invocation`].
+pub const EVENT_LIMIT_EXCEEDED: Status =
+    Status::from_type_and_code(ScStatusType::HostContextError, 100);
+
+/// The status for re-entering a contract while the guard set with
+/// [`Host::set_reentrancy_guard`] is enabled.
+pub const CONTRACT_REENTRY: Status =
+    Status::from_type_and_code(ScStatusType::HostContextError, 101);
+
+/// The status for pushing a frame beyond the depth set with
+/// [`Host::set_max_frame_depth`]. Like a wasm stack overflow, it is not
+/// recoverable.
+pub const MAX_FRAME_DEPTH_EXCEEDED: Status =
+    Status::from_type_and_code(ScStatusType::HostContextError, 102);
+
+/// The status for adding an object beyond the limit set with
+/// [`Host::set_max_objects`].
+pub const OBJECT_LIMIT_EXCEEDED: Status =
+    Status::from_type_and_code(ScStatusType::HostObjectError, 100);
+
+/// The status for a native or test contract that panicked, as opposed to a
+/// wasm contract that trapped.
+pub const NATIVE_CONTRACT_PANIC: Status = Status::from_type_and_code(ScStatusType::VmError, 101);
+
+/// The status for creating a contract, or registering a test contract, under
+/// an ID that is already taken.
+pub const CONTRACT_ALREADY_EXISTS: Status =
+    Status::from_type_and_code(ScStatusType::HostFunctionError, 100);
+
+/// The longest binary, in bytes, that [`Host::validate_scval`] accepts
+/// anywhere in a value.
+pub const MAX_SCVAL_BINARY_LEN: usize = 64 * 1024;
+
 #[derive(Debug, Clone)]
 pub struct LedgerInfo {
     pub protocol_version: u32,
     pub sequence_number: u32,
     pub timestamp: u64,
     pub network_id: Vec<u8>,
+    /// The base reserve of the ledger, in stroops.
+    pub base_reserve: u32,
+    /// The base fee of the ledger, in stroops.
+    pub base_fee: u32,
 }
 
 #[derive(Clone, Default)]
@@ -107,7 +227,29 @@ pub(crate) struct HostImpl {
     ledger: RefCell<Option<LedgerInfo>>,
     objects: RefCell<Vec<HostObject>>,
     storage: RefCell<Storage>,
+    // The storage map as it was when the host was constructed, against which
+    // `storage_changes` reports.
+    baseline_storage: MeteredOrdMap<LedgerKey, Option<LedgerEntry>>,
     context: RefCell<Vec<Frame>>,
+    // `None` means `DEFAULT_MAX_FRAME_DEPTH`.
+    max_frame_depth: RefCell<Option<usize>>,
+    // `None` means `DEFAULT_MAX_OBJECTS`.
+    max_objects: RefCell<Option<usize>>,
+    // When set, a contract may not be pushed while it is already on the stack.
+    reentrancy_guard: RefCell<bool>,
+    // `None` means events are not capped.
+    max_events_per_invocation: RefCell<Option<usize>>,
+    // Contract events recorded since the current top-level invocation started.
+    invocation_events: RefCell<usize>,
+    // Deepest context stack reached since the current top-level invocation
+    // started.
+    peak_frame_depth: RefCell<usize>,
+    // Each contract call in progress, oldest first.
+    calls: RefCell<Vec<CallInfo>>,
+    // When set, wasm code is instantiated before it is stored by contract
+    // creation.
+    #[cfg(feature = "vm")]
+    validate_wasm_on_create: RefCell<bool>,
     // Note: budget is refcounted and is _not_ deep-cloned when you call HostImpl::deep_clone,
     // mainly because it's not really possible to achieve (the same budget is connected to many
     // metered sub-objects) but also because it's plausible that the person calling deep_clone
@@ -160,6 +302,22 @@ impl TryConvert<ScObject, Object> for Host {
 }
 
 impl Host {
+    /// Constructs a new [`Host`] over `storage` with an unlimited [`Budget`].
+    /// This is a convenience for tests; anything metering real transactions
+    /// must set a limit, for example with [`Host::with_budget_limit`].
+    #[cfg(any(test, feature = "testutils"))]
+    pub fn with_storage(storage: Storage) -> Self {
+        let budget = Budget::default();
+        budget.reset_unlimited();
+        Self::with_storage_and_budget(storage, budget)
+    }
+
+    /// Constructs a new [`Host`] over `storage` with a default [`Budget`]
+    /// whose cpu instruction limit is `total`.
+    pub fn with_budget_limit(storage: Storage, total: u64) -> Self {
+        Self::with_storage_and_budget(storage, Budget::default().with_cpu_limit(total))
+    }
+
     /// Constructs a new [`Host`] that will use the provided [`Storage`] for
     /// contract-data access functions such as
     /// [`CheckedEnv::get_contract_data`].
@@ -167,8 +325,18 @@ impl Host {
         Self(Rc::new(HostImpl {
             ledger: RefCell::new(None),
             objects: Default::default(),
+            baseline_storage: storage.map.clone(),
             storage: RefCell::new(storage),
             context: Default::default(),
+            max_frame_depth: Default::default(),
+            max_objects: Default::default(),
+            reentrancy_guard: Default::default(),
+            max_events_per_invocation: Default::default(),
+            invocation_events: Default::default(),
+            peak_frame_depth: Default::default(),
+            calls: Default::default(),
+            #[cfg(feature = "vm")]
+            validate_wasm_on_create: Default::default(),
             budget,
             events: Default::default(),
             #[cfg(feature = "testutils")]
@@ -176,8 +344,121 @@ impl Host {
         }))
     }
 
-    pub fn set_ledger_info(&self, info: LedgerInfo) {
-        *self.0.ledger.borrow_mut() = Some(info)
+    /// Sets the maximum number of frames that may be on the context stack at
+    /// once. Pushing a frame beyond this depth fails with
+    /// [`MAX_FRAME_DEPTH_EXCEEDED`], which bounds recursion well before the
+    /// native stack is at risk.
+    pub fn set_max_frame_depth(&self, depth: usize) {
+        *self.0.max_frame_depth.borrow_mut() = Some(depth)
+    }
+
+    /// Sets the maximum number of host objects. Adding an object beyond this
+    /// fails with [`OBJECT_LIMIT_EXCEEDED`], well before the handle space or
+    /// memory is exhausted. This is a backstop to budget metering, not a
+    /// replacement for it.
+    pub fn set_max_objects(&self, max: usize) {
+        *self.0.max_objects.borrow_mut() = Some(max)
+    }
+
+    /// Returns the maximum number of host objects.
+    pub fn get_max_objects(&self) -> usize {
+        self.0.max_objects.borrow().unwrap_or(DEFAULT_MAX_OBJECTS)
+    }
+
+    /// Returns the deepest the context stack has been during the current or
+    /// most recent top-level invocation, counting its outermost frame.
+    pub fn peak_call_depth(&self) -> Result<u32, HostError> {
+        let peak = *self.0.peak_frame_depth.borrow();
+        self.usize_to_u32(peak, "peak call depth exceeds u32")
+    }
+
+    pub fn get_max_frame_depth(&self) -> usize {
+        self.0
+            .max_frame_depth
+            .borrow()
+            .unwrap_or(DEFAULT_MAX_FRAME_DEPTH)
+    }
+
+    /// Describes the frame on top of the context stack, or returns `None`
+    /// outside of any invocation.
+    pub fn current_frame_info(&self) -> Option<FrameInfo> {
+        self.0.context.borrow().last().map(FrameInfo::from)
+    }
+
+    /// Returns the number of host objects. Objects live until the host is
+    /// dropped or [reset](Host::reset), unless made in a frame that is rolled
+    /// back, so this only ever grows during an invocation that succeeds.
+    pub fn objects_len(&self) -> usize {
+        self.0.objects.borrow().len()
+    }
+
+    /// Returns the number of frames on the context stack.
+    pub fn context_depth(&self) -> usize {
+        self.0.context.borrow().len()
+    }
+
+    /// Enables or disables the reentrancy guard. While enabled, pushing a
+    /// frame for a contract that already has a frame on the context stack
+    /// fails with [`CONTRACT_REENTRY`], so a contract cannot be re-entered
+    /// directly or transitively.
+    /// Disabled by default.
+    pub fn set_reentrancy_guard(&self, enabled: bool) {
+        *self.0.reentrancy_guard.borrow_mut() = enabled
+    }
+
+    /// Caps the number of contract events that may be recorded during a
+    /// single top-level invocation. Recording one more fails with
+    /// [`EVENT_LIMIT_EXCEEDED`]. Debug events are not counted. Uncapped by
+    /// default.
+    pub fn set_max_events_per_invocation(&self, max: usize) {
+        *self.0.max_events_per_invocation.borrow_mut() = Some(max)
+    }
+
+    /// Enables or disables read-only mode, for simulations and view calls.
+    /// While enabled, putting or deleting contract data and emitting contract
+    /// events fail with `ScHostStorageErrorCode::ReadwriteAccessToReadonlyEntry`.
+    /// Debug events are still recorded. Disabled by default, unless the host
+    /// was given storage made with [`Storage::into_read_only`], whose flag
+    /// this sets.
+    pub fn set_read_only(&self, read_only: bool) {
+        self.0.storage.borrow_mut().set_read_only(read_only)
+    }
+
+    /// Enables or disables validation of wasm code when a contract is
+    /// created. While enabled, creating a contract whose code fails to parse
+    /// or instantiate fails with the error `Vm::new` would return, rather than
+    /// storing the code and failing on the first call. Disabled by default.
+    #[cfg(feature = "vm")]
+    pub fn set_validate_wasm_on_create(&self, validate: bool) {
+        *self.0.validate_wasm_on_create.borrow_mut() = validate
+    }
+
+    // Fails if the host is in read-only mode. Storage enforces the same flag
+    // on its own; checking here as well covers contract events and fails
+    // before any work is done.
+    fn check_writable(&self) -> Result<(), HostError> {
+        if self.0.storage.borrow().is_read_only() {
+            return Err(self.err_status_msg(
+                ScHostStorageErrorCode::ReadwriteAccessToReadonlyEntry,
+                "read-only context",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Sets the ledger info read by the `get_ledger_*` host functions. Every
+    /// field, including `base_reserve` and `base_fee`, must be populated with
+    /// the values of the ledger being closed. Fails if `network_id` is not a
+    /// 32-byte hash.
+    pub fn set_ledger_info(&self, info: LedgerInfo) -> Result<(), HostError> {
+        if info.network_id.len() != 32 {
+            return Err(self.err_status_msg(
+                ScHostFnErrorCode::InputArgsWrongLength,
+                "network id must be 32 bytes",
+            ));
+        }
+        *self.0.ledger.borrow_mut() = Some(info);
+        Ok(())
     }
 
     fn with_ledger_info<F, T>(&self, f: F) -> Result<T, HostError>
@@ -190,6 +471,67 @@ impl Host {
         }
     }
 
+    /// Returns a 32-byte seed derived from the current ledger's network ID and
+    /// sequence number and the currently-executing contract ID. The seed is
+    /// deterministic for a given (ledger, contract) pair, so it is only fit
+    /// for uses where that predictability is acceptable.
+    // Notes on metering: charges `ComputeSha256Hash` on the length of the preimage.
+    pub(crate) fn get_prng_seed(&self) -> Result<[u8; 32], HostError> {
+        let contract_id = self.get_current_contract_id()?;
+        let mut preimage = self.with_ledger_info(|li| {
+            let mut buf = li.network_id.clone();
+            buf.extend_from_slice(&li.sequence_number.to_be_bytes());
+            Ok(buf)
+        })?;
+        preimage.extend_from_slice(&contract_id.0);
+        self.charge_budget(CostType::ComputeSha256Hash, preimage.len() as u64)?;
+        Ok(Sha256::digest(&preimage).into())
+    }
+
+    /// Returns a digest of the configuration that affects execution results:
+    /// the ledger protocol version (0 if no ledger info is set), and the
+    /// budget's cost models, limits and modes. Two hosts with equal
+    /// fingerprints are configured identically.
+    // Notes on metering: charges `ComputeSha256Hash` on the length of the preimage.
+    pub fn config_fingerprint(&self) -> Result<Hash, HostError> {
+        let mut buf: Vec<u8> = Vec::new();
+        let protocol_version = self
+            .0
+            .ledger
+            .borrow()
+            .as_ref()
+            .map_or(0, |li| li.protocol_version);
+        buf.extend_from_slice(&protocol_version.to_be_bytes());
+        self.get_budget(|budget| {
+            {
+                let b = budget.0.borrow();
+                for dim in [&b.cpu_insns, &b.mem_bytes] {
+                    buf.extend_from_slice(&dim.get_limit().to_be_bytes());
+                    for ty in CostType::variants() {
+                        let cm = dim.get_cost_model(*ty);
+                        for param in [
+                            cm.const_param,
+                            cm.log_param,
+                            cm.log_base_param,
+                            cm.lin_param,
+                            cm.quad_param,
+                        ] {
+                            buf.extend_from_slice(&param.to_be_bytes());
+                        }
+                    }
+                }
+            }
+            for ty in CostType::variants() {
+                let limit = budget.get_type_limit(*ty);
+                buf.push(limit.is_some() as u8);
+                buf.extend_from_slice(&limit.unwrap_or(0).to_be_bytes());
+            }
+            buf.push(budget.is_dry_run() as u8);
+        });
+        self.charge_budget(CostType::ComputeSha256Hash, buf.len() as u64)?;
+        Ok(Hash(Sha256::digest(&buf).into()))
+    }
+
     /// Helper for mutating the [`Budget`] held in this [`Host`], either to
     /// allocate it on contract creation or to deplete it on callbacks from
     /// the VM or host functions.
@@ -200,6 +542,25 @@ impl Host {
         f(self.0.budget.clone())
     }
 
+    /// Runs `f` with budget charging suspended, so none of the work it does
+    /// counts against the budget, then resumes charging, whether `f` succeeds,
+    /// fails or panics. This is for embedder-privileged operations such as
+    /// system upgrades; it must never be reachable from guest code.
+    pub fn with_budget_exemption<T, F>(&self, f: F) -> Result<T, HostError>
+    where
+        F: FnOnce() -> Result<T, HostError>,
+    {
+        let was_exempt = self.0.budget.set_exempt(true);
+        // A panic may be caught further up, e.g. by `catch_native_panic`, so
+        // charging has to resume before it propagates.
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+        self.0.budget.set_exempt(was_exempt);
+        match res {
+            Ok(res) => res,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+
     pub fn charge_budget(&self, ty: CostType, input: u64) -> Result<(), HostError> {
         self.0.budget.clone().charge(ty, input)
     }
@@ -211,6 +572,22 @@ impl Host {
         f(&mut *self.0.events.borrow_mut())
     }
 
+    // Counts a contract event against `max_events_per_invocation`.
+    fn count_event(&self) -> Result<(), HostError> {
+        let max = *self.0.max_events_per_invocation.borrow();
+        if let Some(max) = max {
+            if *self.0.invocation_events.borrow() >= max {
+                return Err(self.err(
+                    DebugError::new(EVENT_LIMIT_EXCEEDED)
+                        .msg("event limit of {} per invocation exceeded")
+                        .arg(RawVal::from_u32(max as u32)),
+                ));
+            }
+        }
+        *self.0.invocation_events.borrow_mut() += 1;
+        Ok(())
+    }
+
     /// Records a debug event. This in itself is not necessarily an error; it
     /// might just be some contextual event we want to put in a debug log for
     /// diagnostic purpopses. The return value from this is therefore () when
@@ -241,6 +618,8 @@ impl Host {
         topics: ScVec,
         data: ScVal,
     ) -> Result<(), HostError> {
+        self.check_writable()?;
+        self.count_event()?;
         let ce = ContractEvent {
             ext: ExtensionPoint::V0,
             contract_id: self.get_current_contract_id().ok(),
@@ -254,6 +633,34 @@ impl Host {
         self.charge_budget(CostType::HostEventDebug, 1)
     }
 
+    /// Returns the keys of the ledger entries changed (put or deleted) during
+    /// the most recent top-level invocation, excluding writes that were
+    /// rolled back or left an entry as it was. Lets an embedder persist only
+    /// the changed entries. See [`Storage::modified_keys`].
+    // Notes on metering: embedder-facing, not charged.
+    pub fn get_modified_keys(&self) -> Result<Vec<LedgerKey>, HostError> {
+        Ok(self.0.storage.borrow().modified_keys())
+    }
+
+    /// Returns, in key order, every ledger key whose entry differs between
+    /// `base` and the host's current storage. See [Storage::diff].
+    // Notes on metering: covered by `Storage::diff`.
+    pub fn diff_storage(
+        &self,
+        base: &Storage,
+    ) -> Result<Vec<(LedgerKey, StorageDiffKind)>, HostError> {
+        base.diff(&self.0.storage.borrow())
+    }
+
+    /// Returns every entry that has been created, modified or deleted in
+    /// storage since the host was constructed. See [`Storage::changes_since`].
+    pub fn storage_changes(&self) -> Vec<(LedgerKey, Option<LedgerEntry>)> {
+        self.0
+            .storage
+            .borrow()
+            .changes_since(&self.0.baseline_storage)
+    }
+
     pub(crate) fn visit_storage<F, U>(&self, f: F) -> Result<U, HostError>
     where
         F: FnOnce(&mut Storage) -> Result<U, HostError>,
@@ -261,6 +668,71 @@ impl Host {
         f(&mut *self.0.storage.borrow_mut())
     }
 
+    /// Reads the contract data stored under `k`, as with
+    /// [`CheckedEnv::get_contract_data`], and converts it to `T`. A value that
+    /// does not convert fails with [`ScHostValErrorCode::UnexpectedValType`].
+    // Notes on metering: covered by components.
+    pub fn get_contract_data_as<T: TryFromVal<Host, RawVal>>(
+        &self,
+        k: RawVal,
+    ) -> Result<T, HostError> {
+        let v = self.get_contract_data(k)?;
+        T::try_from_val(self, v).map_err(|_| {
+            self.err_status_msg(
+                ScHostValErrorCode::UnexpectedValType,
+                "contract data has unexpected type",
+            )
+        })
+    }
+
+    /// Converts `v` and stores it under `k`, as with
+    /// [`CheckedEnv::put_contract_data`]. A value that does not convert fails
+    /// with [`ScHostValErrorCode::UnexpectedValType`].
+    // Notes on metering: covered by components.
+    pub fn put_contract_data_from<T: TryIntoVal<Host, RawVal>>(
+        &self,
+        k: RawVal,
+        v: T,
+    ) -> Result<(), HostError> {
+        let v = v.try_into_val(self).map_err(|_| {
+            self.err_status_msg(
+                ScHostValErrorCode::UnexpectedValType,
+                "value cannot be stored as contract data",
+            )
+        })?;
+        self.put_contract_data(k, v)?;
+        Ok(())
+    }
+
+    // Notes on metering: covered by components.
+    fn contract_data_ledger_entry(&self, k: RawVal, v: RawVal) -> Result<LedgerEntry, HostError> {
+        let last_modified_ledger_seq = self.with_ledger_info(|li| Ok(li.sequence_number))?;
+        let data = LedgerEntryData::ContractData(ContractDataEntry {
+            contract_id: self.get_current_contract_id()?,
+            key: self.from_host_val(k)?,
+            val: self.from_host_val(v)?,
+        });
+        if let LedgerEntryData::ContractData(ContractDataEntry { key, val, .. }) = &data {
+            self.validate_scval(key)?;
+            self.validate_scval(val)?;
+        }
+        Ok(LedgerEntry {
+            last_modified_ledger_seq,
+            data,
+            ext: LedgerEntryExt::V0,
+        })
+    }
+
+    /// Returns whether a key was written with an expiration that the current
+    /// ledger sequence number has passed. Ledger info is only needed for keys
+    /// that have an expiration.
+    fn is_expired(&self, key: &LedgerKey) -> Result<bool, HostError> {
+        match self.0.storage.borrow().expiration(key) {
+            Some(expire_seq) => self.with_ledger_info(|li| Ok(li.sequence_number > expire_seq)),
+            None => Ok(false),
+        }
+    }
+
     /// Accept a _unique_ (refcount = 1) host reference and destroy the
     /// underlying [`HostImpl`], returning its constituent components to the
     /// caller as a tuple wrapped in `Ok(...)`. If the provided host reference
@@ -276,6 +748,71 @@ impl Host {
             .map_err(Host)
     }
 
+    /// Returns the host to the state it had before any invocation, so it can
+    /// be reused for another: objects, events and per-invocation counters are
+    /// discarded, while storage, ledger info, configuration and the budget
+    /// are kept. Any [`Object`] handles obtained earlier become invalid.
+    ///
+    /// Calling this while a frame is on the context stack is an error. The
+    /// budget's counters are not touched; reset them separately through
+    /// [`Host::get_budget`] if each invocation should be metered afresh.
+    pub fn reset(&self) -> Result<(), HostError> {
+        if !self.0.context.borrow().is_empty() {
+            return Err(self.err_status_msg(
+                ScHostContextErrorCode::UnknownError,
+                "cannot reset host during an invocation",
+            ));
+        }
+        self.0.objects.borrow_mut().clear();
+        self.0.calls.borrow_mut().clear();
+        *self.0.events.borrow_mut() = Events::default();
+        *self.0.invocation_events.borrow_mut() = 0;
+        *self.0.peak_frame_depth.borrow_mut() = 0;
+        Ok(())
+    }
+
+    /// Like [`EnvBase::deep_clone`], but charges the budget for the copy
+    /// first, so that it can be offered to callers that must be metered.
+    /// Nothing is cloned if the charge fails.
+    // Notes on metering: one `HostObjAllocSlot` per object, plus one entry per
+    // vec or map element (each of which is rewired to the new host), plus the
+    // bytes of binary-backed objects, plus `CloneEvents` on the event count.
+    // Storage is an `OrdMap`, so cloning it is cheap structural sharing.
+    pub fn try_deep_clone_metered(&self) -> Result<Host, HostError> {
+        {
+            let objects = self.0.objects.borrow();
+            self.charge_budget(CostType::HostObjAllocSlot, objects.len() as u64)?;
+            for hobj in objects.iter() {
+                match hobj {
+                    HostObject::Vec(v) => {
+                        self.charge_budget(CostType::ImVecMutEntry, v.len() as u64)?
+                    }
+                    HostObject::Map(m) => {
+                        self.charge_budget(CostType::ImMapMutEntry, m.len() as u64)?
+                    }
+                    HostObject::Bin(b) => {
+                        self.charge_budget(CostType::BytesClone, b.len() as u64)?
+                    }
+                    HostObject::BigInt(bi) => {
+                        self.charge_budget(CostType::BytesClone, (bi.bits() + 7) / 8)?
+                    }
+                    HostObject::Hash(_) | HostObject::PublicKey(_) => {
+                        self.charge_budget(CostType::BytesClone, 32)?
+                    }
+                    HostObject::ContractCode(ScContractCode::Wasm(w)) => {
+                        self.charge_budget(CostType::BytesClone, w.len() as u64)?
+                    }
+                    HostObject::U64(_)
+                    | HostObject::I64(_)
+                    | HostObject::ContractCode(ScContractCode::Token) => (),
+                }
+            }
+        }
+        let events = self.0.events.borrow().0.len();
+        self.charge_budget(CostType::CloneEvents, events as u64)?;
+        Ok(self.deep_clone())
+    }
+
     /// Helper function for [`Host::with_frame`] below. Pushes a new [`Frame`]
     /// on the context stack, returning a [`RollbackPoint`] such that if
     /// operation fails, it can be used to roll the [`Host`] back to the state
@@ -284,16 +821,63 @@ impl Host {
         // Charges 1 unit instead of `map.len()` units because of OrdMap's
         // sub-structure sharing that makes cloning cheap.
         self.charge_budget(CostType::PushFrame, 1)?;
+        if self.0.context.borrow().len() >= self.get_max_frame_depth() {
+            return Err(self.err(
+                DebugError::new(MAX_FRAME_DEPTH_EXCEEDED).msg("maximum frame depth exceeded"),
+            ));
+        }
+        if *self.0.reentrancy_guard.borrow() {
+            if let Some(id) = frame.contract_id() {
+                let reentered = self
+                    .0
+                    .context
+                    .borrow()
+                    .iter()
+                    .any(|f| f.contract_id() == Some(id));
+                if reentered {
+                    return Err(self.err(
+                        DebugError::new(CONTRACT_REENTRY).msg("contract re-entry is not allowed"),
+                    ));
+                }
+            }
+        }
         self.0.context.borrow_mut().push(frame);
-        Ok(RollbackPoint {
+        let depth = self.0.context.borrow().len();
+        let mut peak = self.0.peak_frame_depth.borrow_mut();
+        *peak = (*peak).max(depth);
+        drop(peak);
+        Ok(self.rollback_point())
+    }
+
+    /// Captures the [`Host`]'s objects, storage map and contract events, for
+    /// [`Host::rollback`] to restore.
+    fn rollback_point(&self) -> RollbackPoint {
+        RollbackPoint {
             objects: self.0.objects.borrow().len(),
             storage: self.0.storage.borrow().map.clone(),
-        })
+            expirations: self.0.storage.borrow().expirations.clone(),
+            events: self.0.events.borrow().0.len(),
+        }
+    }
+
+    /// Rolls the [`Host`]'s objects, storage map and contract events back to
+    /// the state in the provided [`RollbackPoint`]. Debug events are kept.
+    fn rollback(&self, rp: RollbackPoint) {
+        // Objects are append-only, so those made since the rollback point are
+        // exactly the ones past it. Objects made in frames that succeed are
+        // never reclaimed: their handles may be held anywhere, including guest
+        // memory, so there is no way to prove them unreachable before the host
+        // itself is dropped or reset.
+        self.0.objects.borrow_mut().truncate(rp.objects);
+        self.0.storage.borrow_mut().map = rp.storage;
+        self.0.storage.borrow_mut().expirations = rp.expirations;
+        self.0.events.borrow_mut().rollback(rp.events);
     }
 
     /// Helper function for [`Host::with_frame`] below. Pops a [`Frame`] off
-    /// the current context and optionally rolls back the [`Host`]'s objects
-    /// and storage map to the state in the provided [`RollbackPoint`].
+    /// the current context and optionally rolls back the [`Host`]'s objects,
+    /// storage map and contract events to the state in the provided
+    /// [`RollbackPoint`]. Debug events are kept across a rollback.
     fn pop_frame(&self, orp: Option<RollbackPoint>) -> Result<(), HostError> {
         self.charge_budget(CostType::PopFrame, 1)?;
         self.0
@@ -302,8 +886,7 @@ impl Host {
             .pop()
             .expect("unmatched host frame push/pop");
         if let Some(rp) = orp {
-            self.0.objects.borrow_mut().truncate(rp.objects);
-            self.0.storage.borrow_mut().map = rp.storage;
+            self.rollback(rp);
         }
         Ok(())
     }
@@ -337,6 +920,13 @@ impl Host {
     {
         self.charge_budget(CostType::GuardFrame, 1)?;
         let start_depth = self.0.context.borrow().len();
+        if start_depth == 0 {
+            // A new top-level invocation starts tracking writes and counting
+            // events afresh.
+            self.0.storage.borrow_mut().clear_modified_keys();
+            *self.0.invocation_events.borrow_mut() = 0;
+            *self.0.peak_frame_depth.borrow_mut() = 0;
+        }
         let rp = self.push_frame(frame)?;
         let res = f();
         if res.is_err() {
@@ -352,6 +942,67 @@ impl Host {
         res
     }
 
+    /// Runs `f` in the current [`Frame`], rolling the [`Host`] back as
+    /// [`Host::with_frame`] does if it fails, without pushing a frame.
+    // Notes on metering: charged like guarding a frame.
+    pub(crate) fn with_rollback<F, U>(&self, f: F) -> Result<U, HostError>
+    where
+        F: FnOnce() -> Result<U, HostError>,
+    {
+        self.charge_budget(CostType::GuardFrame, 1)?;
+        let rp = self.rollback_point();
+        let res = f();
+        if res.is_err() {
+            self.rollback(rp);
+        }
+        res
+    }
+
+    /// Like [`Host::with_frame`], but first captures `func` and `args` as the
+    /// function and arguments of the contract call running in `frame`, for
+    /// `get_current_call_func` and `get_current_call_args`.
+    pub(crate) fn with_call_frame<F, U>(
+        &self,
+        frame: Frame,
+        func: Option<Symbol>,
+        args: &[RawVal],
+        f: F,
+    ) -> Result<U, HostError>
+    where
+        F: FnOnce() -> Result<U, HostError>,
+    {
+        let mut captured = HostVec::new(self.0.budget.clone())?;
+        for a in args {
+            captured.push_back(self.associate_raw_val(*a))?;
+        }
+        let depth = self.0.context.borrow().len() + 1;
+        self.0.calls.borrow_mut().push(CallInfo {
+            depth,
+            func,
+            args: captured,
+        });
+        let res = self.with_frame(frame, f);
+        self.0.calls.borrow_mut().pop();
+        res
+    }
+
+    /// Applies a function to the contract call running in the top [`Frame`]
+    /// of the context stack. Returns [`HostError`] if that frame is not a
+    /// contract call.
+    fn with_current_call<F, U>(&self, f: F) -> Result<U, HostError>
+    where
+        F: FnOnce(&CallInfo) -> Result<U, HostError>,
+    {
+        let depth = self.0.context.borrow().len();
+        match self.0.calls.borrow().last() {
+            Some(call) if call.depth == depth => f(call),
+            _ => Err(self.err(
+                DebugError::new(ScHostContextErrorCode::NoContractRunning)
+                    .msg("current frame is not a contract call"),
+            )),
+        }
+    }
+
     /// Returns [`Hash`] contract ID from the VM frame at the top of the context
     /// stack, or a [`HostError`] if the context stack is empty or has a non-VM
     /// frame at its top.
@@ -437,6 +1088,81 @@ impl Host {
         self.0.events.borrow().metered_clone(&self.0.budget)
     }
 
+    /// Converts every object in the host's object table to its [`ScObject`]
+    /// form, in handle order, for comparing one run against a replay.
+    // Notes on metering: each conversion is charged by `from_host_obj`.
+    pub fn dump_objects(&self) -> Result<Vec<ScObject>, HostError> {
+        let types: Vec<ScObjectType> = self
+            .0
+            .objects
+            .borrow()
+            .iter()
+            .map(HostObject::get_type)
+            .collect();
+        types
+            .into_iter()
+            .enumerate()
+            .map(|(handle, ty)| self.from_host_obj(Object::from_type_and_handle(ty, handle as u32)))
+            .collect()
+    }
+
+    /// Serializes an [`ScVal`] to XDR without adding it to the host's objects.
+    // Notes on metering: the serialized size is computed up front, so
+    // `ValSer` is charged in full before anything is written.
+    pub fn serialize_scval(&self, v: &ScVal) -> Result<Vec<u8>, HostError> {
+        let len = xdr_size::scval_xdr_len(v);
+        self.charge_budget(CostType::ValSer, len)?;
+        let mut buf = Vec::<u8>::with_capacity(len as usize);
+        v.write_xdr(&mut buf)
+            .map_err(|_| self.err_general("failed to serialize ScVal"))?;
+        Ok(buf)
+    }
+
+    /// Deserializes an [`ScVal`] from XDR without adding it to the host's
+    /// objects. Fails if the value nests more than 64 levels deep.
+    // Notes on metering: charges `ValDeser` on the input length.
+    pub fn deserialize_scval(&self, bytes: &[u8]) -> Result<ScVal, HostError> {
+        self.charge_budget(CostType::ValDeser, bytes.len() as u64)?;
+        // Reject deep nesting before `read_xdr` recurses into it and before
+        // any host objects are built from it.
+        match xdr_size::check_scval_xdr_depth(bytes, xdr_size::MAX_SCVAL_XDR_DEPTH) {
+            Ok(()) => (),
+            Err(xdr_size::XdrScanError::TooDeep) => {
+                return Err(self.err_status_msg(
+                    ScHostFnErrorCode::InputArgsInvalid,
+                    "ScVal nesting exceeds the maximum depth",
+                ))
+            }
+            Err(xdr_size::XdrScanError::Malformed) => {
+                return Err(self.err_general("failed to de-serialize ScVal"))
+            }
+        }
+        ScVal::read_xdr(&mut &bytes[..])
+            .map_err(|_| self.err_general("failed to de-serialize ScVal"))
+    }
+
+    /// Returns the total size in bytes of the XDR encodings of the contract
+    /// and system events recorded so far, excluding debug events. Embedders
+    /// can price event storage from this.
+    // Notes on metering: embedder-facing, not charged.
+    pub fn total_event_bytes(&self) -> Result<u64, HostError> {
+        let res = self.0.events.borrow().0.iter().try_fold(
+            0u64,
+            |total, e| -> Result<u64, crate::xdr::Error> {
+                match e {
+                    HostEvent::Contract(ce) => {
+                        let mut buf = Vec::new();
+                        ce.write_xdr(&mut buf)?;
+                        Ok(total + buf.len() as u64)
+                    }
+                    HostEvent::Debug(_) => Ok(total),
+                }
+            },
+        );
+        // The events are no longer borrowed here, so the error may be recorded.
+        self.map_err(res)
+    }
+
     // Notes on metering: free
     #[cfg(feature = "vm")]
     fn decode_vmslice(&self, pos: RawVal, len: RawVal) -> Result<VmSlice, HostError> {
@@ -600,6 +1326,23 @@ impl Host {
         Ok(ho)
     }
 
+    /// Applies the checked arithmetic `op` to the values of two u64 or i64
+    /// objects, returning the result as a new object, or a
+    /// [`ScVmErrorCode::TrapIntegerOverflow`] error if `op` returns `None`.
+    // Notes on metering: covered by `visit_obj` and `add_host_object`.
+    fn checked_obj_arith<HOT, F>(&self, a: Object, b: Object, op: F) -> Result<Object, HostError>
+    where
+        HOT: HostObjectType + Copy,
+        F: FnOnce(HOT, HOT) -> Option<HOT>,
+    {
+        let a: HOT = self.visit_obj(a, |x: &HOT| Ok(*x))?;
+        let b: HOT = self.visit_obj(b, |x: &HOT| Ok(*x))?;
+        let res = op(a, b).ok_or_else(|| {
+            self.err_status_msg(ScVmErrorCode::TrapIntegerOverflow, "integer overflow")
+        })?;
+        Ok(self.add_host_object(res)?.into())
+    }
+
     /// Moves a value of some type implementing [`HostObjectType`] into the host's
     /// object array, returning a [`HostObj`] containing the new object's array
     /// index, tagged with the [`xdr::ScObjectType`] and associated with the current
@@ -614,6 +1357,9 @@ impl Host {
         if handle > u32::MAX as usize {
             return Err(self.err_status(ScHostObjErrorCode::ObjectCountExceedsU32Max));
         }
+        if handle >= self.get_max_objects() {
+            return Err(self.err_status_msg(OBJECT_LIMIT_EXCEEDED, "host object limit reached"));
+        }
         self.0
             .objects
             .borrow_mut()
@@ -623,20 +1369,32 @@ impl Host {
         Ok(EnvVal { env, val: v })
     }
 
+    /// Stores `contract` as the code of the contract with binary id `id_obj`,
+    /// failing if that contract already exists. Returns `id_obj`.
     // Notes on metering: this is covered by the called components.
     pub fn create_contract_with_id(
         &self,
         contract: ScContractCode,
         id_obj: Object,
-    ) -> Result<(), HostError> {
+    ) -> Result<Object, HostError> {
         let new_contract_id = self.hash_from_obj_input("id_obj", id_obj)?;
         let storage_key =
             self.contract_code_ledger_key(new_contract_id.metered_clone(&self.0.budget)?);
         if self.0.storage.borrow_mut().has(&storage_key)? {
-            return Err(self.err_general("Contract already exists"));
+            return Err(self.err_status_msg(CONTRACT_ALREADY_EXISTS, "contract already exists"));
+        }
+        #[cfg(feature = "vm")]
+        if let ScContractCode::Wasm(wasm) = &contract {
+            if *self.0.validate_wasm_on_create.borrow() {
+                Vm::new(
+                    self,
+                    new_contract_id.metered_clone(&self.0.budget)?,
+                    wasm.as_slice(),
+                )?;
+            }
         }
         self.store_contract_code(contract, new_contract_id, &storage_key)?;
-        Ok(())
+        Ok(id_obj)
     }
 
     pub fn create_contract_with_id_preimage(
@@ -645,8 +1403,7 @@ impl Host {
         id_preimage: Vec<u8>,
     ) -> Result<Object, HostError> {
         let id_obj = self.compute_hash_sha256(self.add_host_object(id_preimage)?.into())?;
-        self.create_contract_with_id(contract, id_obj)?;
-        Ok(id_obj)
+        self.create_contract_with_id(contract, id_obj)
     }
 
     // Notes on metering: this is covered by the called components.
@@ -666,10 +1423,56 @@ impl Host {
             }
             #[cfg(not(feature = "vm"))]
             ScContractCode::Wasm(_) => Err(self.err_general("could not dispatch")),
-            ScContractCode::Token => self.with_frame(Frame::Token(id.clone()), || {
-                use crate::native_contract::{NativeContract, Token};
-                Token.call(func, self, args)
-            }),
+            ScContractCode::Token => self.call_token_fn(id, func, args),
+        }
+    }
+
+    // Notes on metering: this is covered by the called components.
+    fn call_token_fn(
+        &self,
+        id: &Hash,
+        func: &Symbol,
+        args: &[RawVal],
+    ) -> Result<RawVal, HostError> {
+        self.with_call_frame(Frame::Token(id.clone()), Some(*func), args, || {
+            use crate::native_contract::{NativeContract, Token};
+            self.catch_native_panic(|| Token.call(func, self, args))
+        })
+    }
+
+    /// Runs a native or test contract, turning a panic into a
+    /// [`NATIVE_CONTRACT_PANIC`] error rather than letting it unwind through
+    /// the host. Borrows of host state are released as the panic unwinds, and
+    /// the caller's frame rolls back whatever the contract had done.
+    fn catch_native_panic<F>(&self, f: F) -> Result<RawVal, HostError>
+    where
+        F: FnOnce() -> Result<RawVal, HostError>,
+    {
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+            Ok(res) => res,
+            Err(_) => Err(self.err_status_msg(NATIVE_CONTRACT_PANIC, "native contract panicked")),
+        }
+    }
+
+    /// Calls `func` on the native token contract with id `token_id`, without
+    /// going through the generic dispatch of [`Host::call`]. Fails with
+    /// `InputArgsInvalid` if the contract stored under `token_id` is not a
+    /// token.
+    // Notes on metering: this is covered by the called components.
+    pub fn call_token(
+        &self,
+        token_id: Object,
+        func: Symbol,
+        args: &[RawVal],
+    ) -> Result<RawVal, HostError> {
+        let id = self.hash_from_obj_input("token_id", token_id)?;
+        let storage_key = self.contract_code_ledger_key(id.metered_clone(&self.0.budget)?);
+        match self.retrieve_contract_code_from_storage(&storage_key)? {
+            ScContractCode::Token => self.call_token_fn(&id, &func, args),
+            ScContractCode::Wasm(_) => Err(self.err_status_msg(
+                ScHostFnErrorCode::InputArgsInvalid,
+                "contract is not a token",
+            )),
         }
     }
 
@@ -689,10 +1492,17 @@ impl Host {
             // maintains a borrow of self.0.contracts, which can cause borrow errors.
             let cfs_option = self.0.contracts.borrow().get(&id).cloned();
             if let Some(cfs) = cfs_option {
-                return self.with_frame(Frame::TestContract(id.clone()), || {
-                    cfs.call(&func, self, args)
-                        .ok_or_else(|| self.err_general("function not found"))
-                });
+                return self.with_call_frame(
+                    Frame::TestContract(id.clone()),
+                    Some(func),
+                    args,
+                    || {
+                        self.catch_native_panic(|| {
+                            cfs.call(&func, self, args)
+                                .ok_or_else(|| self.err_general("function not found"))
+                        })
+                    },
+                );
             }
         }
 
@@ -765,7 +1575,7 @@ impl Host {
             contracts.insert(hash, contract_fns);
             Ok(())
         } else {
-            Err(self.err_general("vtable already exists"))
+            Err(self.err_status_msg(CONTRACT_ALREADY_EXISTS, "vtable already exists"))
         }
     }
 
@@ -778,38 +1588,154 @@ impl Host {
     ) -> Result<(), HostError> {
         let contract_code =
             ScContractCode::Wasm(contract_wasm.try_into().map_err(|_| self.err_general(""))?);
-        self.create_contract_with_id(contract_code, contract_id)
-    }
-
-    /// Records a `System` contract event. `topics` is expected to be a `SCVec`
-    /// with length <= 4 that cannot contain Vecs, Maps, or Binaries > 32 bytes
-    /// On succes, returns an `SCStatus::Ok`.
-    pub fn system_event(&self, topics: Object, data: RawVal) -> Result<RawVal, HostError> {
-        let topics = self.event_topics_from_host_obj(topics)?;
-        let data = self.from_host_val(data)?;
-        self.record_contract_event(ContractEventType::System, topics, data)?;
-        Ok(OK.into())
-    }
-}
-
-// Notes on metering: these are called from the guest and thus charged on the VM instructions.
-impl EnvBase for Host {
-    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
-        todo!()
+        self.create_contract_with_id(contract_code, contract_id)?;
+        Ok(())
     }
 
-    fn check_same_env(&self, other: &Self) {
-        assert!(Rc::ptr_eq(&self.0, &other.0));
+    /// Seeds storage with `entries`, replacing any already present. With an
+    /// enforcing footprint each key is also made readable and writable, so
+    /// test state can be set up without building a footprint by hand.
+    // "testutils" is not covered by budget metering.
+    #[cfg(feature = "testutils")]
+    pub fn put_ledger_entries(&self, entries: impl IntoIterator<Item = (LedgerKey, LedgerEntry)>) {
+        let mut storage = self.0.storage.borrow_mut();
+        let enforcing = matches!(storage.mode, crate::storage::FootprintMode::Enforcing);
+        for (key, entry) in entries {
+            if enforcing {
+                storage
+                    .footprint
+                    .0
+                    .map
+                    .insert(key.clone(), crate::storage::AccessType::ReadWrite);
+            }
+            storage.map.map.insert(key, Some(entry));
+        }
     }
 
-    fn deep_clone(&self) -> Self {
-        // Step 1: naive deep-clone the HostImpl. At this point some of the
-        // objects in new_host may have WeakHost refs to the old host.
-        let new_host = Host(Rc::new((*self.0).clone()));
-
-        // Step 2: adjust all the objects that have internal WeakHost refs
-        // to point to a weakhost associated with the new host. There are
-        // only a few of these.
+    // Shared by `binary_copy_from_linear_memory` and its strict variant.
+    fn copy_linear_memory_into_binary(
+        &self,
+        b: Object,
+        b_pos: RawVal,
+        lm_pos: RawVal,
+        len: RawVal,
+        grow: bool,
+    ) -> Result<Object, HostError> {
+        #[cfg(not(feature = "vm"))]
+        unimplemented!();
+        #[cfg(feature = "vm")]
+        {
+            let VmSlice { vm, pos, len } = self.decode_vmslice(lm_pos, len)?;
+            let b_pos = u32::try_from(b_pos)?;
+            let mut vnew =
+                self.visit_obj(b, |hv: &Vec<u8>| Ok(hv.metered_clone(&self.0.budget)?))?;
+            let range = self.binary_write_range(&mut vnew, b_pos, len, grow)?;
+            vm.with_memory_access(self, |mem| {
+                self.charge_budget(CostType::VmMemCpy, len as u64)?;
+                Ok(self.map_err(mem.get_into(pos, &mut vnew.as_mut_slice()[range]))?)
+            })?;
+            Ok(self.add_host_object(vnew)?.into())
+        }
+    }
+
+    /// Returns a copy of binary `b` with `mem` written into it at `b_pos`. If
+    /// the write extends past the end of `b`, the copy is first zero-extended
+    /// when `grow` is set, and otherwise this fails with
+    /// [`ScHostObjErrorCode::VecIndexOutOfBound`].
+    // Notes on metering: not charged, like `binary_copy_from_slice`, since it
+    // is only reachable from native code.
+    pub fn try_binary_copy_from_slice(
+        &self,
+        b: Object,
+        b_pos: RawVal,
+        mem: &[u8],
+        grow: bool,
+    ) -> Result<Object, HostError> {
+        let b_pos = self.u32_from_rawval_input("b_pos", b_pos)?;
+        let len = self.usize_to_u32(mem.len(), "slice len exceeds u32")?;
+        let mut vnew = self.visit_obj(b, |hv: &Vec<u8>| Ok(hv.clone()))?;
+        let range = self.binary_write_range(&mut vnew, b_pos, len, grow)?;
+        vnew[range].copy_from_slice(mem);
+        Ok(self.add_host_object(vnew)?.into())
+    }
+
+    // Returns the range of `bin` that `len` bytes written at `b_pos` cover,
+    // zero-extending `bin` to fit them if `grow` is set and failing if not.
+    fn binary_write_range(
+        &self,
+        bin: &mut Vec<u8>,
+        b_pos: u32,
+        len: u32,
+        grow: bool,
+    ) -> Result<core::ops::Range<usize>, HostError> {
+        let end_idx = b_pos.checked_add(len).ok_or_else(|| {
+            self.err_status_msg(ScHostFnErrorCode::InputArgsInvalid, "u32 overflow")
+        })? as usize;
+        if end_idx > bin.len() {
+            if !grow {
+                return Err(self.err_status_msg(
+                    ScHostObjErrorCode::VecIndexOutOfBound,
+                    "write extends past end of binary",
+                ));
+            }
+            bin.resize(end_idx, 0);
+        }
+        Ok(b_pos as usize..end_idx)
+    }
+
+    /// Copies the bytes of binary `b` starting at `b_pos` into `mem`, filling
+    /// it. Fails with [`ScHostObjErrorCode::VecIndexOutOfBound`] if `b` is
+    /// too short, rather than panicking as [`EnvBase::binary_copy_to_slice`]
+    /// does.
+    // Notes on metering: not charged, like `binary_copy_to_slice`, since it
+    // is only reachable from native code.
+    pub fn try_binary_copy_to_slice(
+        &self,
+        b: Object,
+        b_pos: RawVal,
+        mem: &mut [u8],
+    ) -> Result<(), HostError> {
+        let b_pos = self.u32_from_rawval_input("b_pos", b_pos)?;
+        let len = self.usize_to_u32(mem.len(), "slice len exceeds u32")?;
+        self.visit_obj(b, move |hv: &Vec<u8>| {
+            let range = self.valid_range_from_start_span_bound(b_pos, len, hv.len())?;
+            mem.copy_from_slice(&hv[range]);
+            Ok(())
+        })
+    }
+
+    /// Records a `System` contract event. `topics` is expected to be a `SCVec`
+    /// with length <= 4 that cannot contain Vecs, Maps, or Binaries > 32 bytes
+    /// On succes, returns an `SCStatus::Ok`.
+    pub fn system_event(&self, topics: Object, data: RawVal) -> Result<RawVal, HostError> {
+        let topics = self.event_topics_from_host_obj(topics)?;
+        let data = self.from_host_val(data)?;
+        self.record_contract_event(ContractEventType::System, topics, data)?;
+        Ok(OK.into())
+    }
+}
+
+// Notes on metering: these are called from the guest and thus charged on the VM instructions.
+impl EnvBase for Host {
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        todo!()
+    }
+
+    fn check_same_env(&self, other: &Self) {
+        assert!(Rc::ptr_eq(&self.0, &other.0));
+    }
+
+    // Notes on metering: none. The work is proportional to everything the
+    // host holds, so callers that must be metered should use
+    // `Host::try_deep_clone_metered`, which charges for it up front.
+    fn deep_clone(&self) -> Self {
+        // Step 1: naive deep-clone the HostImpl. At this point some of the
+        // objects in new_host may have WeakHost refs to the old host.
+        let new_host = Host(Rc::new((*self.0).clone()));
+
+        // Step 2: adjust all the objects that have internal WeakHost refs
+        // to point to a weakhost associated with the new host. There are
+        // only a few of these.
         let new_weak = new_host.get_weak();
         for hobj in new_host.0.objects.borrow_mut().iter_mut() {
             match hobj {
@@ -845,39 +1771,19 @@ impl EnvBase for Host {
         // This is also why we _panic_ on errors in here, rather than attempting
         // to return a recoverable error code: native contracts that call this
         // function do so through APIs that _should_ never pass bad data.
+        // Embedders that want the error should call `try_binary_copy_from_slice`.
         //
-        // TODO: we may revisit this choice of panicing in the future, depending
-        // on how we choose to try to contain panics-caused-by-native-contracts.
-        let b_pos = u32::try_from(b_pos).expect("pos input is not u32");
-        let len = u32::try_from(mem.len()).expect("slice len exceeds u32");
-        let mut vnew = self
-            .visit_obj(b, |hv: &Vec<u8>| Ok(hv.clone()))
-            .expect("access to unknown host binary object");
-        let end_idx = b_pos.checked_add(len).expect("u32 overflow") as usize;
-        // TODO: we currently grow the destination vec if it's not big enough,
-        // make sure this is desirable behaviour.
-        if end_idx > vnew.len() {
-            vnew.resize(end_idx, 0);
-        }
-        let write_slice = &mut vnew[b_pos as usize..end_idx];
-        write_slice.copy_from_slice(mem);
-        self.add_host_object(vnew)
-            .expect("unable to add host object")
-            .into()
+        // The destination grows to fit the write, which callers such as the
+        // `[u8; N]` conversion rely on to fill an empty binary.
+        self.try_binary_copy_from_slice(b, b_pos, mem, true)
+            .expect("binary_copy_from_slice failed")
     }
 
     fn binary_copy_to_slice(&self, b: Object, b_pos: RawVal, mem: &mut [u8]) {
-        let b_pos = u32::try_from(b_pos).expect("pos input is not u32");
-        let len = u32::try_from(mem.len()).expect("slice len exceeds u32");
-        self.visit_obj(b, move |hv: &Vec<u8>| {
-            let end_idx = b_pos.checked_add(len).expect("u32 overflow") as usize;
-            if end_idx > hv.len() {
-                panic!("index out of bounds");
-            }
-            mem.copy_from_slice(&hv.as_slice()[b_pos as usize..end_idx]);
-            Ok(())
-        })
-        .expect("access to unknown host object");
+        // Panics for the same reason as `binary_copy_from_slice`; embedders
+        // that want the error should call `try_binary_copy_to_slice`.
+        self.try_binary_copy_to_slice(b, b_pos, mem)
+            .expect("binary_copy_to_slice failed")
     }
 
     fn binary_new_from_slice(&self, mem: &[u8]) -> Object {
@@ -944,12 +1850,28 @@ impl CheckedEnv for Host {
         Ok(self.add_host_object(<Vec<u8>>::from(hash.0))?.into())
     }
 
-    // FIXME: the `cmp` method is not metered. Need a "metered" version (similar to metered_clone)
-    // and use that.
+    // Notes on metering: maps and vecs are charged `HostMapCmp`/`HostVecCmp` on
+    // the shorter length, BigInts `BigIntCmp` on the larger bit-length. Nested
+    // objects are charged as they are compared. Other object types are cheap
+    // and covered by `VisitObject`.
     fn obj_cmp(&self, a: RawVal, b: RawVal) -> Result<i64, HostError> {
         let res = unsafe {
             self.unchecked_visit_val_obj(a, |ao| {
-                self.unchecked_visit_val_obj(b, |bo| Ok(ao.cmp(&bo)))
+                self.unchecked_visit_val_obj(b, |bo| {
+                    match (ao, bo) {
+                        (Some(HostObject::Map(am)), Some(HostObject::Map(bm))) => {
+                            self.charge_budget(CostType::HostMapCmp, am.len().min(bm.len()) as u64)?
+                        }
+                        (Some(HostObject::Vec(av)), Some(HostObject::Vec(bv))) => {
+                            self.charge_budget(CostType::HostVecCmp, av.len().min(bv.len()) as u64)?
+                        }
+                        (Some(HostObject::BigInt(ai)), Some(HostObject::BigInt(bi))) => {
+                            return Ok(ai.metered_cmp(bi)?)
+                        }
+                        _ => (),
+                    }
+                    Ok(ao.cmp(&bo))
+                })
             })?
         };
         Ok(match res {
@@ -959,6 +1881,109 @@ impl CheckedEnv for Host {
         })
     }
 
+    // Notes on metering: charged like `obj_cmp`, except that maps and vecs of
+    // different lengths are found unequal without charging for elements.
+    fn obj_eq(&self, a: RawVal, b: RawVal) -> Result<RawVal, HostError> {
+        let res = unsafe {
+            self.unchecked_visit_val_obj(a, |ao| {
+                self.unchecked_visit_val_obj(b, |bo| {
+                    match (ao, bo) {
+                        (Some(HostObject::Map(am)), Some(HostObject::Map(bm))) => {
+                            if am.len() != bm.len() {
+                                return Ok(false);
+                            }
+                            self.charge_budget(CostType::HostMapCmp, am.len() as u64)?
+                        }
+                        (Some(HostObject::Vec(av)), Some(HostObject::Vec(bv))) => {
+                            if av.len() != bv.len() {
+                                return Ok(false);
+                            }
+                            self.charge_budget(CostType::HostVecCmp, av.len() as u64)?
+                        }
+                        (Some(HostObject::BigInt(ai)), Some(HostObject::BigInt(bi))) => {
+                            return Ok(ai.metered_cmp(bi)? == Ordering::Equal)
+                        }
+                        _ => (),
+                    }
+                    Ok(ao == bo)
+                })
+            })?
+        };
+        Ok(self.bool_to_rawval(res))
+    }
+
+    // Notes on metering: see `map_diff` and `vec_diff`.
+    fn obj_diff(&self, old: RawVal, new: RawVal) -> Result<Object, HostError> {
+        let old: Object = old
+            .try_into()
+            .map_err(|_| self.err_status(ScHostObjErrorCode::UnexpectedType))?;
+        let new: Object = new
+            .try_into()
+            .map_err(|_| self.err_status(ScHostObjErrorCode::UnexpectedType))?;
+        let diff = unsafe {
+            self.unchecked_visit_val_obj(old.into(), |oo| {
+                self.unchecked_visit_val_obj(new.into(), |no| match (oo, no) {
+                    (Some(HostObject::Map(om)), Some(HostObject::Map(nm))) => self.map_diff(om, nm),
+                    (Some(HostObject::Vec(ov)), Some(HostObject::Vec(nv))) => self.vec_diff(ov, nv),
+                    _ => Err(self.err_status(ScHostObjErrorCode::UnexpectedType)),
+                })
+            })?
+        };
+        let added = self.add_host_object(diff.added)?;
+        let removed = self.add_host_object(diff.removed)?;
+        let changed = self.add_host_object(diff.changed)?;
+        let mut vnew = HostVec::new(self.0.budget.clone())?;
+        vnew.push_back(self.associate_raw_val(added.to_raw()))?;
+        vnew.push_back(self.associate_raw_val(removed.to_raw()))?;
+        vnew.push_back(self.associate_raw_val(changed.to_raw()))?;
+        Ok(self.add_host_object(vnew)?.into())
+    }
+
+    // Notes on metering: see `map_apply_diff` and `vec_apply_diff`.
+    fn obj_apply_diff(&self, base: RawVal, diff: Object) -> Result<RawVal, HostError> {
+        let base: Object = base
+            .try_into()
+            .map_err(|_| self.err_status(ScHostObjErrorCode::UnexpectedType))?;
+        let parts = self.visit_obj(diff, |hv: &HostVec| {
+            if hv.len() != 3 {
+                return Err(self.err_status(ScHostObjErrorCode::UnexpectedType));
+            }
+            hv.iter()
+                .map(|e| {
+                    e.to_raw()
+                        .try_into()
+                        .map_err(|_| self.err_status(ScHostObjErrorCode::UnexpectedType))
+                })
+                .collect::<Result<Vec<Object>, HostError>>()
+        })?;
+        let (added, removed, changed) = (parts[0], parts[1], parts[2]);
+        let new: HostObject = self.visit_obj(added, |added: &HostMap| {
+            self.visit_obj(removed, |removed: &HostVec| {
+                self.visit_obj(changed, |changed: &HostMap| unsafe {
+                    self.unchecked_visit_val_obj(base.into(), |bo| match bo {
+                        Some(HostObject::Map(bm)) => Ok(HostObject::Map(
+                            self.map_apply_diff(bm, added, removed, changed)?,
+                        )),
+                        Some(HostObject::Vec(bv)) => Ok(HostObject::Vec(
+                            self.vec_apply_diff(bv, added, removed, changed)?,
+                        )),
+                        _ => Err(self.err_status(ScHostObjErrorCode::UnexpectedType)),
+                    })
+                })
+            })
+        })?;
+        match new {
+            HostObject::Map(m) => Ok(self.add_host_object(m)?.to_raw()),
+            HostObject::Vec(v) => Ok(self.add_host_object(v)?.to_raw()),
+            _ => Err(self.err_general("unexpected diff result")),
+        }
+    }
+
+    // Notes on metering: free.
+    fn is_read_only(&self) -> Result<RawVal, HostError> {
+        Ok(self.bool_to_rawval(self.0.storage.borrow().is_read_only()))
+    }
+
     fn contract_event(&self, topics: Object, data: RawVal) -> Result<RawVal, HostError> {
         let topics = self.event_topics_from_host_obj(topics)?;
         let data = self.from_host_val(data)?;
@@ -966,6 +1991,43 @@ impl CheckedEnv for Host {
         Ok(OK.into())
     }
 
+    // Notes on metering: each contract ID is charged as a metered clone, and
+    // each element push is charged by `push_back`, so the cost is proportional
+    // to the stack depth.
+    fn get_call_stack(&self) -> Result<Object, HostError> {
+        let ids = self
+            .0
+            .context
+            .borrow()
+            .iter()
+            .filter_map(|frame| match frame {
+                #[cfg(feature = "vm")]
+                Frame::ContractVM(vm) => Some(vm.contract_id.metered_clone(&self.0.budget)),
+                Frame::HostFunction(_) => None,
+                Frame::Token(id) => Some(id.metered_clone(&self.0.budget)),
+                #[cfg(feature = "testutils")]
+                Frame::TestContract(id) => Some(id.metered_clone(&self.0.budget)),
+            })
+            .collect::<Result<Vec<Hash>, HostError>>()?;
+        let mut vnew = HostVec::new(self.0.budget.clone())?;
+        for id in ids {
+            let bin = self.add_host_object(<Vec<u8>>::from(id.0))?;
+            vnew.push_back(self.associate_raw_val(bin.to_raw()))?;
+        }
+        Ok(self.add_host_object(vnew)?.into())
+    }
+
+    // Notes on metering: covered by `metered_clone` and `add_host_object`.
+    fn get_current_call_args(&self) -> Result<Object, HostError> {
+        let args = self.with_current_call(|call| call.args.metered_clone(&self.0.budget))?;
+        Ok(self.add_host_object(args)?.into())
+    }
+
+    // Notes on metering: free
+    fn get_current_call_func(&self) -> Result<RawVal, HostError> {
+        self.with_current_call(|call| Ok(call.func.map_or_else(RawVal::from_void, Into::into)))
+    }
+
     // Notes on metering: covered by the components.
     fn get_current_contract(&self) -> Result<Object, HostError> {
         let hash: Hash = self.get_current_contract_id()?;
@@ -992,6 +2054,36 @@ impl CheckedEnv for Host {
         self.visit_obj(obj, |i: &i64| Ok(*i))
     }
 
+    // Notes on metering: covered by `checked_obj_arith`.
+    fn u64_checked_add(&self, a: Object, b: Object) -> Result<Object, HostError> {
+        self.checked_obj_arith(a, b, u64::checked_add)
+    }
+
+    // Notes on metering: covered by `checked_obj_arith`.
+    fn u64_checked_sub(&self, a: Object, b: Object) -> Result<Object, HostError> {
+        self.checked_obj_arith(a, b, u64::checked_sub)
+    }
+
+    // Notes on metering: covered by `checked_obj_arith`.
+    fn u64_checked_mul(&self, a: Object, b: Object) -> Result<Object, HostError> {
+        self.checked_obj_arith(a, b, u64::checked_mul)
+    }
+
+    // Notes on metering: covered by `checked_obj_arith`.
+    fn i64_checked_add(&self, a: Object, b: Object) -> Result<Object, HostError> {
+        self.checked_obj_arith(a, b, i64::checked_add)
+    }
+
+    // Notes on metering: covered by `checked_obj_arith`.
+    fn i64_checked_sub(&self, a: Object, b: Object) -> Result<Object, HostError> {
+        self.checked_obj_arith(a, b, i64::checked_sub)
+    }
+
+    // Notes on metering: covered by `checked_obj_arith`.
+    fn i64_checked_mul(&self, a: Object, b: Object) -> Result<Object, HostError> {
+        self.checked_obj_arith(a, b, i64::checked_mul)
+    }
+
     fn map_new(&self) -> Result<Object, HostError> {
         Ok(self
             .add_host_object(HostMap::new(self.0.budget.clone())?)?
@@ -1133,6 +2225,168 @@ impl CheckedEnv for Host {
         })
     }
 
+    // Notes on metering: iterates the smaller map and probes the larger one, so
+    // the number of lookups charged is proportional to the smaller map's size.
+    fn map_key_intersection_count(&self, a: Object, b: Object) -> Result<RawVal, HostError> {
+        let count = self.visit_obj(a, |ha: &HostMap| {
+            self.visit_obj(b, |hb: &HostMap| {
+                let (small, large) = if ha.len() <= hb.len() {
+                    (ha, hb)
+                } else {
+                    (hb, ha)
+                };
+                let mut count: usize = 0;
+                for k in small.keys()? {
+                    if large.contains_key(k)? {
+                        count += 1;
+                    }
+                }
+                Ok(count)
+            })
+        })?;
+        self.usize_to_rawval_u32(count)
+    }
+
+    // Notes on metering: the scan is charged one `ImMapImmutEntry` on the map's
+    // size, and each removal is charged by `remove`.
+    fn map_remove_default_values(&self, m: Object, default: RawVal) -> Result<Object, HostError> {
+        let default = self.associate_raw_val(default);
+        let mnew = self.visit_obj(m, |hm: &HostMap| {
+            self.charge_budget(CostType::ImMapImmutEntry, hm.len() as u64)?;
+            let mut mnew = hm.metered_clone(&self.0.budget)?;
+            for (k, v) in hm.iter() {
+                if *v == default {
+                    mnew.remove(k)?;
+                }
+            }
+            Ok(mnew)
+        })?;
+        Ok(self.add_host_object(mnew)?.into())
+    }
+
+    // Notes on metering: the scan is charged one `ImMapImmutEntry` on the map's
+    // size. Object values are compared with `obj_cmp`, which charges its own way.
+    fn map_min_value(&self, m: Object) -> Result<RawVal, HostError> {
+        self.visit_obj(m, |hm: &HostMap| {
+            self.charge_budget(CostType::ImMapImmutEntry, hm.len() as u64)?;
+            match hm.iter().map(|(_, v)| v).min() {
+                Some(v) => Ok(v.to_raw()),
+                None => Ok(RawVal::from_void()),
+            }
+        })
+    }
+
+    // Notes on metering: see `map_min_value`.
+    fn map_max_value(&self, m: Object) -> Result<RawVal, HostError> {
+        self.visit_obj(m, |hm: &HostMap| {
+            self.charge_budget(CostType::ImMapImmutEntry, hm.len() as u64)?;
+            match hm.iter().map(|(_, v)| v).max() {
+                Some(v) => Ok(v.to_raw()),
+                None => Ok(RawVal::from_void()),
+            }
+        })
+    }
+
+    // Notes on metering: see `map_min_value`.
+    fn map_argmin(&self, m: Object) -> Result<RawVal, HostError> {
+        self.visit_obj(m, |hm: &HostMap| {
+            self.charge_budget(CostType::ImMapImmutEntry, hm.len() as u64)?;
+            let mut best: Option<(&HostVal, &HostVal)> = None;
+            for (k, v) in hm.iter() {
+                match best {
+                    Some((_, bv)) if v >= bv => (),
+                    _ => best = Some((k, v)),
+                }
+            }
+            match best {
+                Some((k, _)) => Ok(k.to_raw()),
+                None => Ok(RawVal::from_void()),
+            }
+        })
+    }
+
+    // Notes on metering: see `map_min_value`.
+    fn map_argmax(&self, m: Object) -> Result<RawVal, HostError> {
+        self.visit_obj(m, |hm: &HostMap| {
+            self.charge_budget(CostType::ImMapImmutEntry, hm.len() as u64)?;
+            let mut best: Option<(&HostVal, &HostVal)> = None;
+            for (k, v) in hm.iter() {
+                match best {
+                    Some((_, bv)) if v <= bv => (),
+                    _ => best = Some((k, v)),
+                }
+            }
+            match best {
+                Some((k, _)) => Ok(k.to_raw()),
+                None => Ok(RawVal::from_void()),
+            }
+        })
+    }
+
+    // Notes on metering: conversion and serialization are charged by their
+    // components, hashing `ComputeSha256Hash` on the encoded length.
+    fn map_checksum(&self, m: Object) -> Result<Object, HostError> {
+        // Host maps are kept in key order, so the encoding is canonical.
+        self.visit_obj(m, |_: &HostMap| Ok(()))?;
+        let scv = ScVal::Object(Some(self.from_host_obj(m)?));
+        let buf = self.serialize_scval(&scv)?;
+        self.charge_budget(CostType::ComputeSha256Hash, buf.len() as u64)?;
+        let hash: Vec<u8> = Sha256::digest(&buf).to_vec();
+        Ok(self.add_host_object(hash)?.into())
+    }
+
+    // Notes on metering: the scan is charged one `ImMapImmutEntry` on the
+    // map's size, vec-key lookups and inserts by their components.
+    fn map_filter_keys_by_symbol(&self, m: Object, sym: Symbol) -> Result<Object, HostError> {
+        let is_sym = |v: RawVal| v.get_payload() == sym.to_raw().get_payload();
+        let filtered = self.visit_obj(m, |hm: &HostMap| {
+            self.charge_budget(CostType::ImMapImmutEntry, hm.len() as u64)?;
+            let mut out = HostMap::new(self.0.budget.clone())?;
+            for (k, v) in hm.iter() {
+                let matches = match Object::try_from(k.val) {
+                    Ok(obj) if obj.is_obj_type(ScObjectType::Vec) => self
+                        .visit_obj(obj, |hv: &HostVec| {
+                            Ok(hv.front().map_or(false, |e| is_sym(e.val)))
+                        })?,
+                    Ok(_) => false,
+                    Err(_) => is_sym(k.val),
+                };
+                if matches {
+                    out.insert(k.clone(), v.clone())?;
+                }
+            }
+            Ok(out)
+        })?;
+        Ok(self.add_host_object(filtered)?.into())
+    }
+
+    // Notes on metering: one `ImMapMutEntry` on the entry count; the map is
+    // built directly rather than through a metered insert per entry.
+    fn map_from_kv_vecs(&self, keys: Object, vals: Object) -> Result<Object, HostError> {
+        let map = self.visit_obj(keys, |hk: &HostVec| {
+            self.visit_obj(vals, |hv: &HostVec| {
+                if hk.len() != hv.len() {
+                    return Err(self.err_status_msg(
+                        ScHostFnErrorCode::InputArgsWrongLength,
+                        "keys and values differ in length",
+                    ));
+                }
+                self.charge_budget(CostType::ImMapMutEntry, hk.len() as u64)?;
+                let mut mm = OrdMap::new();
+                for (k, v) in hk.iter().zip(hv.iter()) {
+                    if mm.insert(k.clone(), v.clone()).is_some() {
+                        return Err(self.err_status_msg(
+                            ScHostFnErrorCode::InputArgsInvalid,
+                            "duplicate map key",
+                        ));
+                    }
+                }
+                HostMap::from_map(self.0.budget.clone(), mm)
+            })
+        })?;
+        Ok(self.add_host_object(map)?.into())
+    }
+
     fn vec_new(&self, c: RawVal) -> Result<Object, HostError> {
         let capacity: usize = if c.is_void() {
             0
@@ -1236,34 +2490,117 @@ impl CheckedEnv for Host {
         Ok(self.add_host_object(vnew)?.into())
     }
 
+    // Notes on metering: covered by the metered vector operations.
+    fn vec_reverse(&self, v: Object) -> Result<Object, HostError> {
+        let vnew = self.visit_obj(v, |hv: &HostVec| {
+            let mut vnew = hv.metered_clone(&self.0.budget)?;
+            vnew.reverse()?;
+            Ok(vnew)
+        })?;
+        Ok(self.add_host_object(vnew)?.into())
+    }
+
+    // Notes on metering: covered by the metered vector operations.
+    fn vec_sort(&self, v: Object) -> Result<Object, HostError> {
+        let vnew = self.visit_obj(v, |hv: &HostVec| {
+            let mut vnew = hv.metered_clone(&self.0.budget)?;
+            vnew.sort()?;
+            Ok(vnew)
+        })?;
+        Ok(self.add_host_object(vnew)?.into())
+    }
+
+    // Notes on metering: covered by the metered vector operations.
+    fn vec_dedup(&self, v: Object) -> Result<Object, HostError> {
+        let vnew = self.visit_obj(v, |hv: &HostVec| {
+            let mut vnew = hv.metered_clone(&self.0.budget)?;
+            vnew.dedup()?;
+            Ok(vnew)
+        })?;
+        Ok(self.add_host_object(vnew)?.into())
+    }
+
+    // Notes on metering: one `ImVecMutEntry` per byte.
+    fn vec_from_binary(&self, b: Object) -> Result<Object, HostError> {
+        let vec = self.visit_obj(b, |hv: &Vec<u8>| {
+            self.charge_budget(CostType::ImVecMutEntry, hv.len() as u64)?;
+            Ok(hv
+                .iter()
+                .map(|x| self.associate_raw_val(u32::from(*x).into()))
+                .collect::<Vector<HostVal>>())
+        })?;
+        Ok(self
+            .add_host_object(HostVec::from_vec(self.0.budget.clone(), vec)?)?
+            .into())
+    }
+
+    // Notes on metering: each element is charged a `VisitObject` by `visit_obj`.
+    fn vec_is_contiguous_u64(&self, v: Object) -> Result<RawVal, HostError> {
+        self.visit_obj(v, |hv: &HostVec| {
+            let mut prev: Option<u64> = None;
+            for e in hv.iter() {
+                let obj: Object = e
+                    .to_raw()
+                    .try_into()
+                    .map_err(|_| self.err_status(ScHostObjErrorCode::UnexpectedType))?;
+                let u = self.visit_obj(obj, |u: &u64| Ok(*u))?;
+                if let Some(p) = prev {
+                    if p.checked_add(1) != Some(u) {
+                        return Ok(false.into());
+                    }
+                }
+                prev = Some(u);
+            }
+            Ok(true.into())
+        })
+    }
+
     // Notes on metering: covered by components
     fn put_contract_data(&self, k: RawVal, v: RawVal) -> Result<RawVal, HostError> {
+        self.check_writable()?;
         let key = self.contract_data_key_from_rawval(k)?;
-        let data = LedgerEntryData::ContractData(ContractDataEntry {
-            contract_id: self.get_current_contract_id()?,
-            key: self.from_host_val(k)?,
-            val: self.from_host_val(v)?,
-        });
-        let val = LedgerEntry {
-            last_modified_ledger_seq: 0,
-            data,
-            ext: LedgerEntryExt::V0,
-        };
+        let val = self.contract_data_ledger_entry(k, v)?;
         self.0.storage.borrow_mut().put(&key, &val)?;
         Ok(().into())
     }
 
+    // Notes on metering: covered by components
+    fn put_contract_data_with_expiration(
+        &self,
+        k: RawVal,
+        v: RawVal,
+        expire_seq: RawVal,
+    ) -> Result<RawVal, HostError> {
+        self.check_writable()?;
+        let expire_seq = u32::try_from(expire_seq)
+            .map_err(|_| self.err_status(ScHostFnErrorCode::InputArgsWrongType))?;
+        let key = self.contract_data_key_from_rawval(k)?;
+        let val = self.contract_data_ledger_entry(k, v)?;
+        self.0
+            .storage
+            .borrow_mut()
+            .put_with_expiration(&key, &val, expire_seq)?;
+        Ok(().into())
+    }
+
     // Notes on metering: covered by components
     fn has_contract_data(&self, k: RawVal) -> Result<RawVal, HostError> {
         let key = self.storage_key_from_rawval(k)?;
         let res = self.0.storage.borrow_mut().has(&key)?;
-        Ok(RawVal::from_bool(res))
+        Ok(self.bool_to_rawval(res && !self.is_expired(&key)?))
     }
 
     // Notes on metering: covered by components
     fn get_contract_data(&self, k: RawVal) -> Result<RawVal, HostError> {
         let key = self.storage_key_from_rawval(k)?;
-        match self.0.storage.borrow_mut().get(&key)?.data {
+        let entry = self.0.storage.borrow_mut().get(&key)?;
+        if self.is_expired(&key)? {
+            return Err(self.err_status_msg(
+                ScHostStorageErrorCode::MissingKeyInGet,
+                "contract data has expired",
+            ));
+        }
+        match entry.data {
             LedgerEntryData::ContractData(ContractDataEntry {
                 contract_id,
                 key,
@@ -1276,13 +2613,105 @@ impl CheckedEnv for Host {
         }
     }
 
+    // Notes on metering: covered by components
+    fn try_get_contract_data(&self, k: RawVal) -> Result<RawVal, HostError> {
+        if self.bool_from_rawval_input("has", self.has_contract_data(k)?)? {
+            self.get_contract_data(k)
+        } else {
+            Ok(().into())
+        }
+    }
+
+    // Notes on metering: covered by components
+    fn is_token_contract(&self, contract: Object) -> Result<RawVal, HostError> {
+        let id = self.hash_from_obj_input("contract", contract)?;
+        let key = self.contract_code_ledger_key(id);
+        let code = self.retrieve_contract_code_from_storage(&key)?;
+        Ok(matches!(code, ScContractCode::Token).into())
+    }
+
+    // Notes on metering: covered by components
+    fn has_contract_code(&self, contract: Object) -> Result<RawVal, HostError> {
+        let id = self.hash_from_obj_input("contract", contract)?;
+        let key = self.contract_code_ledger_key(id);
+        Ok(self.0.storage.borrow_mut().has(&key)?.into())
+    }
+
+    // Notes on metering: the storage read is covered by components, the hash
+    // is charged `ComputeSha256Hash` on the code length.
+    fn get_contract_code_hash(&self, contract: Object) -> Result<Object, HostError> {
+        let id = self.hash_from_obj_input("contract", contract)?;
+        let key = self.contract_code_ledger_key(id);
+        let wasm = match self.retrieve_contract_code_from_storage(&key)? {
+            ScContractCode::Wasm(wasm) => wasm,
+            ScContractCode::Token => {
+                return Err(self.err_status_msg(
+                    ScHostFnErrorCode::InputArgsInvalid,
+                    "token contract has no wasm code",
+                ))
+            }
+        };
+        self.charge_budget(CostType::ComputeSha256Hash, wasm.len() as u64)?;
+        let hash: Vec<u8> = Sha256::digest(wasm.as_slice()).to_vec();
+        Ok(self.add_host_object(hash)?.into())
+    }
+
     // Notes on metering: covered by components
     fn del_contract_data(&self, k: RawVal) -> Result<RawVal, HostError> {
+        self.check_writable()?;
         let key = self.contract_data_key_from_rawval(k)?;
         self.0.storage.borrow_mut().del(&key)?;
         Ok(().into())
     }
 
+    // Expired entries are deleted and counted too: they are hidden from reads
+    // but still stored, and would otherwise outlive the contract.
+    // Notes on metering: the scan is charged one `ImMapImmutEntry` on the
+    // storage map's size, and each deletion is charged by `del`.
+    fn destroy_current_contract(&self) -> Result<RawVal, HostError> {
+        self.check_writable()?;
+        let contract_id = self.get_current_contract_id()?;
+        let count = self.visit_storage(|storage| {
+            storage.map.charge_immut_access(storage.map.len() as u64)?;
+            let keys: Vec<LedgerKey> = storage
+                .map
+                .iter()
+                .filter_map(|(k, v)| match (k, v) {
+                    (
+                        LedgerKey::ContractData(LedgerKeyContractData {
+                            contract_id: id, ..
+                        }),
+                        Some(_),
+                    ) if *id == contract_id => Some(k.clone()),
+                    _ => None,
+                })
+                .collect();
+            for key in keys.iter() {
+                storage.del(key)?;
+            }
+            Ok(keys.len())
+        })?;
+        self.usize_to_rawval_u32(count)
+    }
+
+    // Notes on metering: the scan is charged one `ImMapImmutEntry` per
+    // matching entry, conversions and pushes by the components.
+    fn get_contract_data_keys(&self) -> Result<Object, HostError> {
+        let contract_id = self.get_current_contract_id()?;
+        let keys = self.visit_storage(|storage| storage.contract_data_keys(&contract_id))?;
+        let mut vec = HostVec::new(self.0.budget.clone())?;
+        for lk in keys.iter() {
+            // Expired entries are hidden, as from `has_contract_data`.
+            if self.is_expired(lk)? {
+                continue;
+            }
+            if let LedgerKey::ContractData(LedgerKeyContractData { key, .. }) = lk {
+                vec.push_back(self.to_host_val(key)?)?;
+            }
+        }
+        Ok(self.add_host_object(vec)?.into())
+    }
+
     // Notes on metering: covered by the components.
     fn create_contract_from_ed25519(
         &self,
@@ -1295,17 +2724,11 @@ impl CheckedEnv for Host {
         let key_val = self.uint256_from_obj_input("key", key)?;
 
         // Verify parameters
-        let params = self.visit_obj(v, |bin: &Vec<u8>| {
+        let hash = self.visit_obj(v, |bin: &Vec<u8>| {
             let separator = "create_contract_from_ed25519(contract: Vec<u8>, salt: u256, key: u256, sig: Vec<u8>)";
-            let params = [separator.as_bytes(), salt_val.as_ref(), bin].concat();
-            // Another charge-after-work. Easier to get the num bytes this way.
-            // TODO: 1. pre calcualte the bytes and charge before concat. 
-            // 2. Might be overkill to have a separate type for this. Maybe can consolidate
-            // with `BytesClone` or `BytesAppend`.
-            self.charge_budget(CostType::BytesConcat, params.len() as u64)?;
-            Ok(params)
+            self.compute_id_preimage_hash(&[separator.as_bytes(), salt_val.as_ref(), bin])
         })?;
-        let hash = self.compute_hash_sha256(self.add_host_object(params)?.into())?;
+        let hash = self.add_host_object(hash)?.into();
 
         self.verify_sig_ed25519(hash, key, sig)?;
 
@@ -1334,6 +2757,34 @@ impl CheckedEnv for Host {
         self.create_contract_with_id_preimage(wasm, buf)
     }
 
+    // Notes on metering: covered by the components.
+    fn create_and_init_contract(
+        &self,
+        v: Object,
+        salt: Object,
+        init_fn: Symbol,
+        init_args: Object,
+    ) -> Result<Object, HostError> {
+        let contract_id = self.get_current_contract_id()?;
+        let salt = self.uint256_from_obj_input("salt", salt)?;
+
+        let wasm = self.visit_obj(v, |b: &Vec<u8>| {
+            Ok(ScContractCode::Wasm(
+                b.try_into()
+                    .map_err(|_| self.err_general("code too large"))?,
+            ))
+        })?;
+        let buf = self.id_preimage_from_contract(contract_id, salt)?;
+        // Deploy and initialize under a rollback of their own, so a failed
+        // initialization undoes the deploy before the caller sees it. No frame
+        // is pushed, so the init function sees the caller as its invoker.
+        self.with_rollback(|| {
+            let id = self.create_contract_with_id_preimage(wasm, buf)?;
+            self.call(id, init_fn, init_args)?;
+            Ok(id)
+        })
+    }
+
     fn create_token_from_ed25519(
         &self,
         salt: Object,
@@ -1344,13 +2795,9 @@ impl CheckedEnv for Host {
         let key_val = self.uint256_from_obj_input("key", key)?;
 
         // Verify parameters
-        let params = {
-            let separator = "create_token_from_ed25519(salt: u256, key: u256, sig: Vec<u8>)";
-            [separator.as_bytes(), salt_val.as_ref()].concat()
-        };
-        // Another charge-after-work. Easier to get the num bytes this way.
-        self.charge_budget(CostType::BytesConcat, params.len() as u64)?;
-        let hash = self.compute_hash_sha256(self.add_host_object(params)?.into())?;
+        let separator = "create_token_from_ed25519(salt: u256, key: u256, sig: Vec<u8>)";
+        let hash = self.compute_id_preimage_hash(&[separator.as_bytes(), salt_val.as_ref()])?;
+        let hash = self.add_host_object(hash)?.into();
 
         self.verify_sig_ed25519(hash, key, sig)?;
 
@@ -1375,12 +2822,18 @@ impl CheckedEnv for Host {
     }
 
     // Notes on metering: covered by the components.
+    // On failure, records a debug event naming the callee contract, function
+    // and status. Debug events recorded by the callee survive the rollback of
+    // its frame, so they precede this one in the event log.
     fn try_call(&self, contract: Object, func: Symbol, args: Object) -> Result<RawVal, HostError> {
         match self.call(contract, func, args) {
             Ok(rv) => Ok(rv),
+            Err(e) if !e.is_recoverable() => Err(e),
             Err(e) => {
                 let evt = DebugEvent::new()
-                    .msg("try_call got error from callee contract")
+                    .msg("try_call to contract {} function {} failed with status {}")
+                    .arg::<RawVal>(contract.into())
+                    .arg::<RawVal>(func.into())
                     .arg::<RawVal>(e.status.clone().into());
                 self.record_debug_event(evt)?;
                 Ok(e.status.into())
@@ -1417,6 +2870,60 @@ impl CheckedEnv for Host {
         })
     }
 
+    // Notes on metering: covered by `i128_from_obj_input` and object adding.
+    fn bigint_from_i128(&self, x: Object) -> Result<Object, HostError> {
+        let i = self.i128_from_obj_input("x", x)?;
+        Ok(self
+            .add_host_object(MeteredBigInt::from_i128(self.0.budget.clone(), i)?)?
+            .into())
+    }
+
+    // Notes on metering: new object adding is covered. Conversion from BigInt to i128 is free.
+    fn bigint_to_i128(&self, x: Object) -> Result<Object, HostError> {
+        let i = self.visit_obj(x, |bi: &MeteredBigInt| {
+            bi.to_i128()
+                .ok_or_else(|| self.err_conversion_into_rawval::<i128>(x.into()))
+        })?;
+        self.add_i128_object(i)
+    }
+
+    // Notes on metering: covered by object adding.
+    fn obj_from_i128_pieces(&self, lo: u64, hi: u64) -> Result<Object, HostError> {
+        self.add_i128_object((((hi as u128) << 64) | lo as u128) as i128)
+    }
+
+    // Notes on metering: covered by `i128_from_obj_input`.
+    fn obj_to_i128_lo64(&self, obj: Object) -> Result<u64, HostError> {
+        Ok(self.i128_from_obj_input("obj", obj)? as u64)
+    }
+
+    // Notes on metering: covered by `i128_from_obj_input`.
+    fn obj_to_i128_hi64(&self, obj: Object) -> Result<u64, HostError> {
+        Ok((self.i128_from_obj_input("obj", obj)? as u128 >> 64) as u64)
+    }
+
+    // Notes on metering: covered by `i128_from_obj_input` and object adding;
+    // the arithmetic is constant-time and free.
+    fn i128_add(&self, a: Object, b: Object) -> Result<Object, HostError> {
+        let a = self.i128_from_obj_input("a", a)?;
+        let b = self.i128_from_obj_input("b", b)?;
+        let sum = a.checked_add(b).ok_or_else(|| {
+            self.err_status_msg(ScVmErrorCode::TrapIntegerOverflow, "i128 overflow")
+        })?;
+        self.add_i128_object(sum)
+    }
+
+    // Notes on metering: covered by `i128_from_obj_input` and object adding;
+    // the arithmetic is constant-time and free.
+    fn i128_mul(&self, a: Object, b: Object) -> Result<Object, HostError> {
+        let a = self.i128_from_obj_input("a", a)?;
+        let b = self.i128_from_obj_input("b", b)?;
+        let product = a.checked_mul(b).ok_or_else(|| {
+            self.err_status_msg(ScVmErrorCode::TrapIntegerOverflow, "i128 overflow")
+        })?;
+        self.add_i128_object(product)
+    }
+
     // Notes on metering: fully covered.
     // Notes on calibration: use equal length objects to get the result upper bound.
     fn bigint_add(&self, x: Object, y: Object) -> Result<Object, HostError> {
@@ -1448,6 +2955,13 @@ impl CheckedEnv for Host {
         Ok(self.add_host_object(res)?.into())
     }
 
+    // Notes on metering: charges `BigIntMul` on the number of u64 words in `x`.
+    fn bigint_mul_u32(&self, x: Object, scalar: RawVal) -> Result<Object, HostError> {
+        let scalar = self.u32_from_rawval_input("scalar", scalar)?;
+        let res = self.visit_obj(x, |a: &MeteredBigInt| a.mul_u32(scalar))?;
+        Ok(self.add_host_object(res)?.into())
+    }
+
     // Notes on metering and model calibration:
     // Use uneven length numbers for the upper bound measurement.
     fn bigint_div(&self, x: Object, y: Object) -> Result<Object, HostError> {
@@ -1516,7 +3030,7 @@ impl CheckedEnv for Host {
     // TODO: this function will be removed
     fn bigint_cmp(&self, x: Object, y: Object) -> Result<RawVal, HostError> {
         self.visit_obj(x, |a: &MeteredBigInt| {
-            self.visit_obj(y, |b: &MeteredBigInt| Ok((a.cmp(b) as i32).into()))
+            self.visit_obj(y, |b: &MeteredBigInt| Ok((a.metered_cmp(b)? as i32).into()))
         })
     }
 
@@ -1577,6 +3091,99 @@ impl CheckedEnv for Host {
         self.visit_obj(x, |a: &MeteredBigInt| Ok(a.bits()))
     }
 
+    // Notes on metering: each element is charged a `VisitObject` by `visit_obj`.
+    fn bigint_vec_max_bits(&self, v: Object) -> Result<RawVal, HostError> {
+        let max = self.visit_obj(v, |hv: &HostVec| {
+            let mut max: u64 = 0;
+            for e in hv.iter() {
+                let x: Object = e
+                    .to_raw()
+                    .try_into()
+                    .map_err(|_| self.err_status(ScHostObjErrorCode::UnexpectedType))?;
+                max = max.max(self.visit_obj(x, |b: &MeteredBigInt| Ok(b.bits()))?);
+            }
+            Ok(max)
+        })?;
+        let max =
+            u32::try_from(max).map_err(|_| self.err_status(ScHostValErrorCode::U32OutOfRange))?;
+        Ok(max.into())
+    }
+
+    // Notes on metering: the running sum starts with one `BigIntNew`. Each
+    // element is then charged by the steps that fold it in: a `BigIntAddSub`
+    // on the larger bit-length of the sum and the element, which is not
+    // bounded by the modulus, a `BigIntDivRem` for reducing the result, and a
+    // second `BigIntAddSub` when the remainder is negative.
+    fn bigint_vec_sum_mod(&self, v: Object, modulus: Object) -> Result<Object, HostError> {
+        let res = self.visit_obj(modulus, |m: &MeteredBigInt| {
+            if m.is_zero() || m.is_negative() {
+                return Err(self.err_status_msg(
+                    ScHostFnErrorCode::InputArgsInvalid,
+                    "modulus must be positive",
+                ));
+            }
+            self.visit_obj(v, |hv: &HostVec| {
+                let mut sum = MeteredBigInt::new(self.0.budget.clone())?;
+                for e in hv.iter() {
+                    let x: Object = e
+                        .to_raw()
+                        .try_into()
+                        .map_err(|_| self.err_status(ScHostObjErrorCode::UnexpectedType))?;
+                    sum = self.visit_obj(x, |b: &MeteredBigInt| sum.add(b)?.rem(m))?;
+                    // `rem` keeps the sign of the dividend.
+                    if sum.is_negative() {
+                        sum = sum.add(m)?;
+                    }
+                }
+                Ok(sum)
+            })
+        })?;
+        Ok(self.add_host_object(res)?.into())
+    }
+
+    // Notes on metering: each element is charged the chosen operation, as in
+    // the corresponding single-step host function.
+    fn bigint_vec_fold(&self, v: Object, init: Object, op: RawVal) -> Result<Object, HostError> {
+        let op = self.u32_from_rawval_input("op", op)?;
+        if op > BIGINT_FOLD_OR {
+            return Err(self.err_status_msg(
+                ScHostFnErrorCode::InputArgsInvalid,
+                "unknown fold operation",
+            ));
+        }
+        let mut acc = self.visit_obj(init, |a: &MeteredBigInt| a.metered_clone(&self.0.budget))?;
+        let elems = self.visit_obj(v, |hv: &HostVec| {
+            hv.iter()
+                .map(|e| {
+                    e.to_raw()
+                        .try_into()
+                        .map_err(|_| self.err_status(ScHostObjErrorCode::UnexpectedType))
+                })
+                .collect::<Result<Vec<Object>, HostError>>()
+        })?;
+        for x in elems {
+            acc = self.visit_obj(x, |b: &MeteredBigInt| match op {
+                BIGINT_FOLD_ADD => acc.add(b),
+                BIGINT_FOLD_MUL => acc.mul(b),
+                BIGINT_FOLD_MIN | BIGINT_FOLD_MAX => {
+                    let keep_acc = match acc.metered_cmp(b)? {
+                        Ordering::Less => op == BIGINT_FOLD_MIN,
+                        Ordering::Greater => op == BIGINT_FOLD_MAX,
+                        Ordering::Equal => true,
+                    };
+                    if keep_acc {
+                        acc.metered_clone(&self.0.budget)
+                    } else {
+                        b.metered_clone(&self.0.budget)
+                    }
+                }
+                BIGINT_FOLD_AND => acc.bitand(b),
+                _ => acc.bitor(b),
+            })?;
+        }
+        Ok(self.add_host_object(acc)?.into())
+    }
+
     fn bigint_to_bytes_be(&self, x: Object) -> Result<Object, Self::Error> {
         let sign_bytes = self.visit_obj(x, |a: &MeteredBigInt| a.to_bytes_be())?;
         Ok(self.add_host_object(sign_bytes.1)?.into())
@@ -1591,26 +3198,25 @@ impl CheckedEnv for Host {
     // Notes on metering: covered by components
     fn serialize_to_binary(&self, v: RawVal) -> Result<Object, HostError> {
         let scv = self.from_host_val(v)?;
-        let mut buf = Vec::<u8>::new();
-        scv.write_xdr(&mut buf)
-            .map_err(|_| self.err_general("failed to serialize ScVal"))?;
-        // Notes on metering": "write first charge later" means we could potentially underestimate
-        // the cost by the largest sized host object. Since we are bounding the memory limit of a
-        // host object, it is probably fine.
-        // Ideally, `charge` should go before `write_xdr`, which would require us to either 1.
-        // make serialization an iterative / chunked operation. Or 2. have a XDR method to
-        // calculate the serialized size. Both would require non-trivial XDR changes.
-        self.charge_budget(CostType::ValSer, buf.len() as u64)?;
+        let buf = self.serialize_scval(&scv)?;
         Ok(self.add_host_object(buf)?.into())
     }
 
+    // Notes on metering: covered by `rawval_xdr_len`.
+    fn obj_serialized_size(&self, v: RawVal) -> Result<RawVal, HostError> {
+        let len = self.rawval_xdr_len(v)?;
+        let len = u32::try_from(len).map_err(|_| {
+            self.err_status_msg(
+                ScHostFnErrorCode::InputArgsInvalid,
+                "serialized size exceeds u32",
+            )
+        })?;
+        Ok(len.into())
+    }
+
     // Notes on metering: covered by components
     fn deserialize_from_binary(&self, b: Object) -> Result<RawVal, HostError> {
-        let scv = self.visit_obj(b, |hv: &Vec<u8>| {
-            self.charge_budget(CostType::ValDeser, hv.len() as u64)?;
-            ScVal::read_xdr(&mut hv.as_slice())
-                .map_err(|_| self.err_general("failed to de-serialize ScVal"))
-        })?;
+        let scv = self.visit_obj(b, |hv: &Vec<u8>| self.deserialize_scval(hv))?;
         Ok(self.to_host_val(&scv)?.into())
     }
 
@@ -1645,30 +3251,17 @@ impl CheckedEnv for Host {
         lm_pos: RawVal,
         len: RawVal,
     ) -> Result<Object, HostError> {
-        #[cfg(not(feature = "vm"))]
-        unimplemented!();
-        #[cfg(feature = "vm")]
-        {
-            let VmSlice { vm, pos, len } = self.decode_vmslice(lm_pos, len)?;
-            let b_pos = u32::try_from(b_pos)?;
-            let mut vnew =
-                self.visit_obj(b, |hv: &Vec<u8>| Ok(hv.metered_clone(&self.0.budget)?))?;
-            let end_idx = b_pos.checked_add(len).ok_or_else(|| {
-                self.err_status_msg(ScHostFnErrorCode::InputArgsInvalid, "u32 overflow")
-            })? as usize;
-            // TODO: we currently grow the destination vec if it's not big enough,
-            // make sure this is desirable behaviour.
-            if end_idx > vnew.len() {
-                vnew.resize(end_idx, 0);
-            }
-            vm.with_memory_access(self, |mem| {
-                self.charge_budget(CostType::VmMemCpy, len as u64)?;
-                Ok(self.map_err(
-                    mem.get_into(pos, &mut vnew.as_mut_slice()[b_pos as usize..end_idx]),
-                )?)
-            })?;
-            Ok(self.add_host_object(vnew)?.into())
-        }
+        self.copy_linear_memory_into_binary(b, b_pos, lm_pos, len, true)
+    }
+
+    fn binary_copy_from_linear_memory_strict(
+        &self,
+        b: Object,
+        b_pos: RawVal,
+        lm_pos: RawVal,
+        len: RawVal,
+    ) -> Result<Object, HostError> {
+        self.copy_linear_memory_into_binary(b, b_pos, lm_pos, len, false)
     }
 
     fn binary_new_from_linear_memory(
@@ -1819,6 +3412,163 @@ impl CheckedEnv for Host {
         Ok(self.add_host_object(vnew)?.into())
     }
 
+    // Notes on metering: the per-byte work is covered by `metered_clone`.
+    fn binary_twos_complement_neg(&self, b: Object) -> Result<Object, HostError> {
+        let vnew = self.visit_obj(b, |hv: &Vec<u8>| {
+            let mut vnew = hv.metered_clone(&self.0.budget)?;
+            // Invert every bit, then add one starting from the least
+            // significant (last) byte, stopping once the carry is absorbed.
+            let mut carry = true;
+            for byte in vnew.iter_mut().rev() {
+                *byte = !*byte;
+                if carry {
+                    let (sum, overflow) = byte.overflowing_add(1);
+                    *byte = sum;
+                    carry = overflow;
+                }
+            }
+            Ok(vnew)
+        })?;
+        Ok(self.add_host_object(vnew)?.into())
+    }
+
+    // Notes on metering: the per-byte work is covered by `metered_clone`.
+    fn binary_add_be(&self, a: Object, b: Object) -> Result<Object, HostError> {
+        let (sum, carry) = self.visit_obj(a, |av: &Vec<u8>| {
+            self.visit_obj(b, |bv: &Vec<u8>| {
+                if av.len() != bv.len() {
+                    return Err(self.err_status_msg(
+                        ScHostFnErrorCode::InputArgsInvalid,
+                        "binaries to add must have equal lengths",
+                    ));
+                }
+                let mut sum = av.metered_clone(&self.0.budget)?;
+                let mut carry = false;
+                for (x, y) in sum.iter_mut().rev().zip(bv.iter().rev()) {
+                    let (s1, o1) = x.overflowing_add(*y);
+                    let (s2, o2) = s1.overflowing_add(carry as u8);
+                    *x = s2;
+                    carry = o1 || o2;
+                }
+                Ok((sum, carry))
+            })
+        })?;
+        let sum = self.add_host_object(sum)?;
+        let mut vnew = HostVec::new(self.0.budget.clone())?;
+        vnew.push_back(self.associate_raw_val(sum.to_raw()))?;
+        vnew.push_back(self.associate_raw_val(carry.into()))?;
+        Ok(self.add_host_object(vnew)?.into())
+    }
+
+    // Notes on metering: charges `BytesClone` on the input length, which
+    // bounds the output length.
+    fn binary_rle_encode(&self, b: Object) -> Result<Object, HostError> {
+        let vnew = self.visit_obj(b, |hv: &Vec<u8>| {
+            self.charge_budget(CostType::BytesClone, hv.len() as u64)?;
+            let mut vnew: Vec<u8> = Vec::new();
+            for byte in hv.iter() {
+                match vnew.as_mut_slice() {
+                    [.., last, count] if *last == *byte && *count < u8::MAX => *count += 1,
+                    _ => vnew.extend_from_slice(&[*byte, 1]),
+                }
+            }
+            Ok(vnew)
+        })?;
+        Ok(self.add_host_object(vnew)?.into())
+    }
+
+    // Notes on metering: charges `BytesClone` on the decoded length, which is
+    // computed before anything is allocated.
+    fn binary_rle_decode(&self, b: Object) -> Result<Object, HostError> {
+        let vnew = self.visit_obj(b, |hv: &Vec<u8>| {
+            if hv.len() % 2 != 0 || hv.chunks(2).any(|pair| pair[1] == 0) {
+                return Err(self.err_status_msg(
+                    ScHostFnErrorCode::InputArgsInvalid,
+                    "malformed run-length encoding",
+                ));
+            }
+            let len: u64 = hv.chunks(2).map(|pair| pair[1] as u64).sum();
+            self.charge_budget(CostType::BytesClone, len)?;
+            let mut vnew: Vec<u8> = Vec::with_capacity(len as usize);
+            for pair in hv.chunks(2) {
+                vnew.resize(vnew.len() + pair[1] as usize, pair[0]);
+            }
+            Ok(vnew)
+        })?;
+        Ok(self.add_host_object(vnew)?.into())
+    }
+
+    // Notes on metering: charges `BytesClone` on the 4 bytes read.
+    fn binary_get_u32_be(&self, b: Object, offset: RawVal) -> Result<RawVal, HostError> {
+        let offset = self.u32_from_rawval_input("offset", offset)?;
+        let val = self.visit_obj(b, |hv: &Vec<u8>| {
+            let range = self.valid_range_from_start_span_bound(offset, 4, hv.len())?;
+            self.charge_budget(CostType::BytesClone, 4)?;
+            let mut word = [0u8; 4];
+            word.copy_from_slice(&hv[range]);
+            Ok(u32::from_be_bytes(word))
+        })?;
+        Ok(val.into())
+    }
+
+    // Notes on metering: the copy is covered by `metered_clone`.
+    fn binary_set_u32_be(
+        &self,
+        b: Object,
+        offset: RawVal,
+        val: RawVal,
+    ) -> Result<Object, HostError> {
+        let offset = self.u32_from_rawval_input("offset", offset)?;
+        let val = self.u32_from_rawval_input("val", val)?;
+        let vnew = self.visit_obj(b, |hv: &Vec<u8>| {
+            let range = self.valid_range_from_start_span_bound(offset, 4, hv.len())?;
+            let mut vnew = hv.metered_clone(&self.0.budget)?;
+            vnew[range].copy_from_slice(&val.to_be_bytes());
+            Ok(vnew)
+        })?;
+        Ok(self.add_host_object(vnew)?.into())
+    }
+
+    // Notes on metering: charges `BytesCmp` on the longer of the two lengths,
+    // including when the lengths differ.
+    fn binary_ct_eq(&self, a: Object, b: Object) -> Result<RawVal, HostError> {
+        let eq = self.visit_obj(a, |va: &Vec<u8>| {
+            self.visit_obj(b, |vb: &Vec<u8>| {
+                self.charge_budget(CostType::BytesCmp, va.len().max(vb.len()) as u64)?;
+                if va.len() != vb.len() {
+                    return Ok(false);
+                }
+                // Accumulate every difference rather than returning at the
+                // first, so the time taken does not depend on where it is.
+                let diff = va
+                    .iter()
+                    .zip(vb.iter())
+                    .fold(0_u8, |acc, (x, y)| acc | (x ^ y));
+                Ok(diff == 0)
+            })
+        })?;
+        Ok(eq.into())
+    }
+
+    // Notes on metering: one `BytesPush` per element.
+    fn binary_from_vec(&self, v: Object) -> Result<Object, HostError> {
+        let bytes = self.visit_obj(v, |hv: &HostVec| {
+            self.charge_budget(CostType::BytesPush, hv.len() as u64)?;
+            hv.iter()
+                .map(|e| {
+                    let x = self.u32_from_rawval_input("v", e.val)?;
+                    u8::try_from(x).map_err(|_| {
+                        self.err_status_msg(
+                            ScHostFnErrorCode::InputArgsInvalid,
+                            "vector element does not fit in a byte",
+                        )
+                    })
+                })
+                .collect::<Result<Vec<u8>, HostError>>()
+        })?;
+        Ok(self.add_host_object(bytes)?.into())
+    }
+
     fn hash_from_binary(&self, x: Object) -> Result<Object, HostError> {
         todo!()
     }
@@ -1841,6 +3591,42 @@ impl CheckedEnv for Host {
         Ok(self.add_host_object(hash)?.into())
     }
 
+    // Notes on metering: each part is charged `ComputeSha256Hash` on its
+    // length as it is fed to the hasher.
+    fn compute_hash_sha256_parts(&self, parts: Object) -> Result<Object, HostError> {
+        let parts = self.visit_obj(parts, |hv: &HostVec| {
+            hv.iter()
+                .map(|e| {
+                    e.to_raw()
+                        .try_into()
+                        .map_err(|_| self.err_status(ScHostObjErrorCode::UnexpectedType))
+                })
+                .collect::<Result<Vec<Object>, HostError>>()
+        })?;
+        let mut hasher = Sha256::new();
+        for part in parts {
+            self.visit_obj(part, |bin: &Vec<u8>| {
+                self.charge_budget(CostType::ComputeSha256Hash, bin.len() as u64)?;
+                hasher.update(bin);
+                Ok(())
+            })?;
+        }
+        let hash: Vec<u8> = hasher.finalize().to_vec();
+        Ok(self.add_host_object(hash)?.into())
+    }
+
+    // Notes on metering: covered by components.
+    fn compute_hash_keccak256(&self, x: Object) -> Result<Object, HostError> {
+        let hash = self.keccak256_hash_from_binary_input(x)?;
+        Ok(self.add_host_object(hash)?.into())
+    }
+
+    // Notes on metering: covered by components.
+    fn compute_hmac_sha256(&self, key: Object, msg: Object) -> Result<Object, HostError> {
+        let mac = self.hmac_sha256_from_binary_inputs(key, msg)?;
+        Ok(self.add_host_object(mac)?.into())
+    }
+
     // Notes on metering: covered by components.
     fn verify_sig_ed25519(&self, x: Object, k: Object, s: Object) -> Result<RawVal, HostError> {
         use ed25519_dalek::Verifier;
@@ -1855,6 +3641,157 @@ impl CheckedEnv for Host {
         Ok(res?.into())
     }
 
+    // Notes on metering: covered by components.
+    fn verify_sig(
+        &self,
+        scheme: RawVal,
+        x: Object,
+        k: Object,
+        s: Object,
+    ) -> Result<RawVal, HostError> {
+        match self.u32_from_rawval_input("scheme", scheme)? {
+            SIG_SCHEME_ED25519 => self.verify_sig_ed25519(x, k, s),
+            SIG_SCHEME_SECP256K1 => {
+                use k256::ecdsa::signature::Verifier;
+                let public_key = self.secp256k1_pub_key_from_obj_input(k)?;
+                let sig = self.secp256k1_signature_from_obj_input("sig", s)?;
+                let res = self.visit_obj(x, |bin: &Vec<u8>| {
+                    self.charge_budget(CostType::VerifySecp256k1Sig, bin.len() as u64)?;
+                    public_key
+                        .verify(bin, &sig)
+                        .map_err(|_| self.err_general("Failed secp256k1 verification"))
+                });
+                Ok(res?.into())
+            }
+            _ => Err(self.err_status_msg(
+                ScHostFnErrorCode::InputArgsInvalid,
+                "unknown signature scheme",
+            )),
+        }
+    }
+
+    // Notes on metering: charges `ComputeSha256Hash` on the length of the
+    // preimage.
+    fn derive_identifier(&self, parent: Object, index: RawVal) -> Result<Object, HostError> {
+        let parent = self.hash_from_obj_input("parent", parent)?;
+        let index = self.u32_from_rawval_input("index", index)?;
+        let mut preimage = parent.0.to_vec();
+        preimage.extend_from_slice(&index.to_be_bytes());
+        self.charge_budget(CostType::ComputeSha256Hash, preimage.len() as u64)?;
+        let child: Vec<u8> = Sha256::digest(&preimage).to_vec();
+        Ok(self.add_host_object(child)?.into())
+    }
+
+    // Notes on metering: every key and non-void signature is parsed, so that
+    // malformed input traps wherever it sits. Each verification attempted is
+    // charged as in `verify_sig_ed25519`; void signatures and those after the
+    // threshold is met are not verified.
+    fn verify_threshold_ed25519(
+        &self,
+        x: Object,
+        keys: Object,
+        sigs: Object,
+        threshold: RawVal,
+    ) -> Result<RawVal, HostError> {
+        use ed25519_dalek::Verifier;
+        let threshold = self.u32_from_rawval_input("threshold", threshold)?;
+        let keys = self.visit_obj(keys, |hv: &HostVec| {
+            Ok(hv.iter().map(|v| v.val).collect::<Vec<RawVal>>())
+        })?;
+        let sigs = self.visit_obj(sigs, |hv: &HostVec| {
+            Ok(hv.iter().map(|v| v.val).collect::<Vec<RawVal>>())
+        })?;
+        if keys.len() != sigs.len() {
+            return Err(self.err_status_msg(
+                ScHostFnErrorCode::InputArgsWrongLength,
+                "keys and sigs differ in length",
+            ));
+        }
+        let mut slots = Vec::with_capacity(keys.len());
+        for (k, s) in keys.into_iter().zip(sigs.into_iter()) {
+            let k: Object = k
+                .try_into()
+                .map_err(|_| self.err_status(ScHostObjErrorCode::UnexpectedType))?;
+            let public_key = self.ed25519_pub_key_from_obj_input(k)?;
+            if s.is_void() {
+                continue;
+            }
+            let s: Object = s
+                .try_into()
+                .map_err(|_| self.err_status(ScHostObjErrorCode::UnexpectedType))?;
+            let sig = self.signature_from_obj_input("sig", s)?;
+            slots.push((public_key, sig));
+        }
+        let mut verified: u32 = 0;
+        for (public_key, sig) in slots {
+            if verified >= threshold {
+                break;
+            }
+            let ok = self.visit_obj(x, |bin: &Vec<u8>| {
+                self.charge_budget(CostType::VerifyEd25519Sig, bin.len() as u64)?;
+                Ok(public_key.verify(bin, &sig).is_ok())
+            })?;
+            if ok {
+                verified += 1;
+            }
+        }
+        Ok(self.bool_to_rawval(verified >= threshold))
+    }
+
+    // Notes on metering: each signature is charged `VerifyEd25519Sig` on the
+    // length of its message, as in `verify_sig_ed25519`, before the batch is
+    // verified.
+    fn verify_sig_ed25519_batch(
+        &self,
+        msgs: Object,
+        keys: Object,
+        sigs: Object,
+    ) -> Result<RawVal, HostError> {
+        let as_objects = |v: Object| {
+            self.visit_obj(v, |hv: &HostVec| {
+                hv.iter()
+                    .map(|e| {
+                        e.to_raw()
+                            .try_into()
+                            .map_err(|_| self.err_status(ScHostObjErrorCode::UnexpectedType))
+                    })
+                    .collect::<Result<Vec<Object>, HostError>>()
+            })
+        };
+        let msgs = as_objects(msgs)?;
+        let keys = as_objects(keys)?;
+        let sigs = as_objects(sigs)?;
+        if msgs.len() != keys.len() || msgs.len() != sigs.len() {
+            return Err(self.err_status_msg(
+                ScHostFnErrorCode::InputArgsWrongLength,
+                "msgs, keys and sigs differ in length",
+            ));
+        }
+        let msgs = msgs
+            .into_iter()
+            .map(|m| {
+                self.visit_obj(m, |bin: &Vec<u8>| {
+                    self.charge_budget(CostType::VerifyEd25519Sig, bin.len() as u64)?;
+                    bin.metered_clone(&self.0.budget)
+                })
+            })
+            .collect::<Result<Vec<Vec<u8>>, HostError>>()?;
+        let keys = keys
+            .into_iter()
+            .map(|k| self.ed25519_pub_key_from_obj_input(k))
+            .collect::<Result<Vec<_>, HostError>>()?;
+        let sigs = sigs
+            .into_iter()
+            .map(|s| self.signature_from_obj_input("sig", s))
+            .collect::<Result<Vec<_>, HostError>>()?;
+        if !msgs.is_empty() {
+            let msgs: Vec<&[u8]> = msgs.iter().map(|m| m.as_slice()).collect();
+            ed25519_dalek::verify_batch(&msgs, &sigs, &keys)
+                .map_err(|_| self.err_general("Failed ED25519 batch verification"))?;
+        }
+        Ok(self.bool_to_rawval(true))
+    }
+
     // Notes on metering: covered by components.
     fn account_get_low_threshold(&self, a: Object) -> Result<RawVal, Self::Error> {
         let threshold = self.load_account(a)?.thresholds.0[ThresholdIndexes::Low as usize];
@@ -1876,7 +3813,9 @@ impl CheckedEnv for Host {
         Ok(threshold.into())
     }
 
-    // Notes on metering: some covered. The for loop and comparisons are free (for now).
+    // Notes on metering: loading the account and cloning the target signer are
+    // covered by the components, and the scan over the account's signers is
+    // charged `AccountSignerScan` on the number of signers.
     fn account_get_signer_weight(&self, a: Object, s: Object) -> Result<RawVal, Self::Error> {
         use xdr::{Signer, SignerKey};
 
@@ -1895,6 +3834,7 @@ impl CheckedEnv for Host {
         } else {
             // Target signer is not the master key, so search the account signers
             let signers: &Vec<Signer> = ae.signers.as_ref();
+            self.charge_budget(CostType::AccountSignerScan, signers.len() as u64)?;
             for signer in signers {
                 if let SignerKey::Ed25519(ref this_signer) = signer.key {
                     if &target_signer == this_signer {
@@ -1908,6 +3848,26 @@ impl CheckedEnv for Host {
         }
     }
 
+    // Notes on metering: covered by `load_account`.
+    fn account_get_num_signers(&self, a: Object) -> Result<RawVal, Self::Error> {
+        let ae = self.load_account(a)?;
+        self.usize_to_rawval_u32(ae.signers.len())
+    }
+
+    // Notes on metering: covered by `load_account` and object adding.
+    fn account_get_signer_key_at(&self, a: Object, i: RawVal) -> Result<Object, Self::Error> {
+        use xdr::SignerKey;
+
+        let i = self.u32_from_rawval_input("i", i)?;
+        let ae = self.load_account(a)?;
+        self.validate_index_lt_bound(i, ae.signers.len())?;
+        let key = match &ae.signers.as_slice()[i as usize].key {
+            SignerKey::Ed25519(k) | SignerKey::PreAuthTx(k) | SignerKey::HashX(k) => k.0,
+            SignerKey::Ed25519SignedPayload(p) => p.ed25519.0,
+        };
+        Ok(self.add_host_object(key.to_vec())?.into())
+    }
+
     fn get_ledger_version(&self) -> Result<RawVal, Self::Error> {
         self.with_ledger_info(|li| Ok(li.protocol_version.into()))
     }
@@ -1920,9 +3880,45 @@ impl CheckedEnv for Host {
         self.with_ledger_info(|li| Ok(self.add_host_object(li.timestamp)?.into()))
     }
 
+    // Notes on metering: free unless the timestamp does not fit in a u63, in
+    // which case object adding is covered.
+    fn get_ledger_timestamp_small(&self) -> Result<RawVal, Self::Error> {
+        let timestamp = self.with_ledger_info(|li| Ok(li.timestamp))?;
+        match i64::try_from(timestamp) {
+            Ok(i) => Ok(unsafe { RawVal::unchecked_from_u63(i) }),
+            Err(_) => Ok(self.add_host_object(timestamp)?.into()),
+        }
+    }
+
     fn get_ledger_network_id(&self) -> Result<Object, Self::Error> {
         Ok(self
             .with_ledger_info(|li| self.add_host_object(li.network_id.clone()))?
             .into())
     }
+
+    fn get_ledger_base_reserve(&self) -> Result<RawVal, Self::Error> {
+        self.with_ledger_info(|li| Ok(li.base_reserve.into()))
+    }
+
+    fn get_ledger_base_fee(&self) -> Result<RawVal, Self::Error> {
+        self.with_ledger_info(|li| Ok(li.base_fee.into()))
+    }
+
+    // The index is the first 8 bytes of the PRNG seed reduced modulo `upper`.
+    // This has a modulo bias of at most `upper / 2^64`, which is negligible for
+    // a u32 bound and acceptable for the raffle-style uses this is meant for.
+    fn random_index(&self, upper: RawVal) -> Result<RawVal, Self::Error> {
+        let upper = self.u32_from_rawval_input("upper", upper)?;
+        if upper == 0 {
+            return Err(self.err_status_msg(
+                ScHostFnErrorCode::InputArgsInvalid,
+                "random_index upper bound must be non-zero",
+            ));
+        }
+        let seed = self.get_prng_seed()?;
+        let mut word = [0u8; 8];
+        word.copy_from_slice(&seed[0..8]);
+        let idx = u64::from_be_bytes(word) % (upper as u64);
+        Ok((idx as u32).into())
+    }
 }
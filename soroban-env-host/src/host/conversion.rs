@@ -11,8 +11,10 @@ use crate::{
 use ed25519_dalek::{PublicKey, Signature, SIGNATURE_LENGTH};
 use num_bigint::Sign;
 use sha2::{Digest, Sha256};
+use sha3::Keccak256;
 
 use super::metered_bigint::MeteredBigInt;
+use super::MeteredClone;
 
 impl Host {
     // Notes on metering: free
@@ -57,6 +59,28 @@ impl Host {
         }
     }
 
+    // Notes on metering: free
+    pub(crate) fn bool_from_rawval_input(
+        &self,
+        name: &'static str,
+        r: RawVal,
+    ) -> Result<bool, HostError> {
+        match bool::try_from(r) {
+            Ok(v) => Ok(v),
+            Err(_) => Err(self.err(
+                DebugError::new(ScHostValErrorCode::UnexpectedValType)
+                    .msg("unexpected RawVal {} for input '{}', need bool")
+                    .arg(r)
+                    .arg(name),
+            )),
+        }
+    }
+
+    // Notes on metering: free
+    pub(crate) fn bool_to_rawval(&self, b: bool) -> RawVal {
+        RawVal::from_bool(b)
+    }
+
     pub(crate) fn to_u256(&self, a: Object) -> Result<Uint256, HostError> {
         self.visit_obj(a, |bin: &Vec<u8>| {
             self.charge_budget(CostType::BytesClone, 32)?;
@@ -107,7 +131,22 @@ impl Host {
         self.fixed_length_binary_from_obj_input::<Signature, SIGNATURE_LENGTH>(name, sig)
     }
 
-    fn fixed_length_binary_from_obj_input<T, const N: usize>(
+    // There is no i128 object type yet, so i128s travel as 16-byte big-endian
+    // two's-complement binaries.
+    pub(crate) fn i128_from_obj_input(
+        &self,
+        name: &'static str,
+        obj: Object,
+    ) -> Result<i128, HostError> {
+        let bytes = self.fixed_length_binary_from_obj_input::<[u8; 16], 16>(name, obj)?;
+        Ok(i128::from_be_bytes(bytes))
+    }
+
+    pub(crate) fn add_i128_object(&self, i: i128) -> Result<Object, HostError> {
+        Ok(self.add_host_object(i.to_be_bytes().to_vec())?.into())
+    }
+
+    pub(crate) fn fixed_length_binary_from_obj_input<T, const N: usize>(
         &self,
         name: &'static str,
         obj: Object,
@@ -141,6 +180,67 @@ impl Host {
         })
     }
 
+    pub fn secp256k1_pub_key_from_obj_input(
+        &self,
+        k: Object,
+    ) -> Result<k256::ecdsa::VerifyingKey, HostError> {
+        self.visit_obj(k, |bin: &Vec<u8>| {
+            self.charge_budget(CostType::BytesClone, bin.len() as u64)?;
+            k256::ecdsa::VerifyingKey::from_sec1_bytes(bin).map_err(|_| {
+                self.err_status_msg(ScHostObjErrorCode::UnexpectedType, "invalid public key")
+            })
+        })
+    }
+
+    pub(crate) fn secp256k1_signature_from_obj_input(
+        &self,
+        name: &'static str,
+        sig: Object,
+    ) -> Result<k256::ecdsa::Signature, HostError> {
+        let bytes: [u8; 64] = self.fixed_length_binary_from_obj_input(name, sig)?;
+        k256::ecdsa::Signature::try_from(bytes.as_slice()).map_err(|_| {
+            self.err_status_msg(ScHostObjErrorCode::UnexpectedType, "invalid signature")
+        })
+    }
+
+    pub fn keccak256_hash_from_binary_input(&self, x: Object) -> Result<Vec<u8>, HostError> {
+        self.visit_obj(x, |bin: &Vec<u8>| {
+            self.charge_budget(CostType::ComputeKeccak256, bin.len() as u64)?;
+            Ok(Keccak256::digest(bin).to_vec())
+        })
+    }
+
+    // HMAC as in RFC 2104, over sha256 with its 64-byte block size.
+    pub fn hmac_sha256_from_binary_inputs(
+        &self,
+        key: Object,
+        msg: Object,
+    ) -> Result<Vec<u8>, HostError> {
+        const BLOCK_LEN: usize = 64;
+        let mut key_block = self.visit_obj(key, |bin: &Vec<u8>| {
+            if bin.len() > BLOCK_LEN {
+                self.charge_budget(CostType::ComputeSha256Hash, bin.len() as u64)?;
+                Ok(Sha256::digest(bin).to_vec())
+            } else {
+                bin.metered_clone(&self.0.budget)
+            }
+        })?;
+        key_block.resize(BLOCK_LEN, 0);
+        let pad = |b: u8| key_block.iter().map(|k| k ^ b).collect::<Vec<u8>>();
+        let inner = self.visit_obj(msg, |bin: &Vec<u8>| {
+            self.charge_budget(CostType::ComputeHmacSha256, bin.len() as u64)?;
+            Ok(Sha256::new()
+                .chain_update(pad(0x36))
+                .chain_update(bin)
+                .finalize())
+        })?;
+        Ok(Sha256::new()
+            .chain_update(pad(0x5c))
+            .chain_update(inner)
+            .finalize()
+            .to_vec())
+    }
+
     pub fn sha256_hash_from_binary_input(&self, x: Object) -> Result<Vec<u8>, HostError> {
         self.visit_obj(x, |bin: &Vec<u8>| {
             self.charge_budget(CostType::ComputeSha256Hash, bin.len() as u64)?;
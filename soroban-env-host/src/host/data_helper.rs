@@ -6,6 +6,7 @@ use crate::xdr::{
     ScStatic, ScVal,
 };
 use crate::{Host, HostError, Object};
+use sha2::{Digest, Sha256};
 use soroban_env_common::xdr::{AccountEntry, AccountId, Hash, PublicKey, Uint256, WriteXdr};
 
 impl Host {
@@ -92,6 +93,21 @@ impl Host {
         Ok(buf)
     }
 
+    // Hashes the concatenation of `parts`, such as a separator, salt and
+    // payload, feeding each to the hasher in turn instead of building the
+    // concatenation.
+    // Notes on metering: charges `ComputeSha256Hash` on the total length
+    // before hashing.
+    pub(crate) fn compute_id_preimage_hash(&self, parts: &[&[u8]]) -> Result<Vec<u8>, HostError> {
+        let len: usize = parts.iter().map(|p| p.len()).sum();
+        self.charge_budget(CostType::ComputeSha256Hash, len as u64)?;
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        Ok(hasher.finalize().to_vec())
+    }
+
     // notes on metering: `get` from storage and `to_u256` covered. Rest are free.
     pub fn load_account(&self, a: Object) -> Result<AccountEntry, HostError> {
         let acc = LedgerKey::Account(LedgerKeyAccount {
@@ -0,0 +1,158 @@
+use super::MeteredClone;
+use crate::budget::CostType;
+use crate::host_object::{HostMap, HostVal, HostVec};
+use crate::xdr::ScHostFnErrorCode;
+use crate::{Host, HostError};
+
+/// The parts of a diff between two maps or two vecs, as produced by
+/// `obj_diff`. Vecs are treated as maps from u32 index to element, so an
+/// element appended to a vec shows up in `added`, one truncated from it in
+/// `removed`, and one overwritten in `changed`.
+pub(crate) struct ObjDiff {
+    /// Keys only in the new value, with their values.
+    pub(crate) added: HostMap,
+    /// Keys only in the old value.
+    pub(crate) removed: HostVec,
+    /// Keys in both values whose values differ, with the new values.
+    pub(crate) changed: HostMap,
+}
+
+impl Host {
+    fn new_obj_diff(&self) -> Result<ObjDiff, HostError> {
+        Ok(ObjDiff {
+            added: HostMap::new(self.0.budget.clone())?,
+            removed: HostVec::new(self.0.budget.clone())?,
+            changed: HostMap::new(self.0.budget.clone())?,
+        })
+    }
+
+    // Notes on metering: the scans are charged one `ImMapImmutEntry` on the
+    // combined size, lookups and inserts by the metered map.
+    pub(crate) fn map_diff(&self, old: &HostMap, new: &HostMap) -> Result<ObjDiff, HostError> {
+        self.charge_budget(CostType::ImMapImmutEntry, (old.len() + new.len()) as u64)?;
+        let mut diff = self.new_obj_diff()?;
+        for (k, v) in new.iter() {
+            match old.get(k)? {
+                None => {
+                    diff.added.insert(k.clone(), v.clone())?;
+                }
+                Some(ov) if ov != v => {
+                    diff.changed.insert(k.clone(), v.clone())?;
+                }
+                Some(_) => (),
+            }
+        }
+        for k in old.keys()? {
+            if !new.contains_key(k)? {
+                diff.removed.push_back(k.clone())?;
+            }
+        }
+        Ok(diff)
+    }
+
+    // Notes on metering: the scans are charged one `ImVecImmutEntry` on the
+    // combined length, inserts and pushes by the metered containers.
+    pub(crate) fn vec_diff(&self, old: &HostVec, new: &HostVec) -> Result<ObjDiff, HostError> {
+        self.charge_budget(CostType::ImVecImmutEntry, (old.len() + new.len()) as u64)?;
+        let mut diff = self.new_obj_diff()?;
+        for (i, (ov, nv)) in old.iter().zip(new.iter()).enumerate() {
+            if ov != nv {
+                diff.changed.insert(self.index_key(i)?, nv.clone())?;
+            }
+        }
+        for (i, nv) in new.iter().enumerate().skip(old.len()) {
+            diff.added.insert(self.index_key(i)?, nv.clone())?;
+        }
+        for i in new.len()..old.len() {
+            diff.removed.push_back(self.index_key(i)?)?;
+        }
+        Ok(diff)
+    }
+
+    fn index_key(&self, i: usize) -> Result<HostVal, HostError> {
+        Ok(self.associate_raw_val(self.usize_to_rawval_u32(i)?))
+    }
+
+    fn index_from_key(&self, k: &HostVal) -> Result<usize, HostError> {
+        match u32::try_from(k.val) {
+            Ok(i) => Ok(i as usize),
+            Err(_) => Err(self.err_inconsistent_diff()),
+        }
+    }
+
+    fn err_inconsistent_diff(&self) -> HostError {
+        self.err_status_msg(
+            ScHostFnErrorCode::InputArgsInvalid,
+            "diff is inconsistent with base",
+        )
+    }
+
+    // Notes on metering: covered by the metered map operations.
+    pub(crate) fn map_apply_diff(
+        &self,
+        base: &HostMap,
+        added: &HostMap,
+        removed: &HostVec,
+        changed: &HostMap,
+    ) -> Result<HostMap, HostError> {
+        let mut new = base.metered_clone(&self.0.budget)?;
+        for k in removed.iter() {
+            if new.remove(k)?.is_none() {
+                return Err(self.err_inconsistent_diff());
+            }
+        }
+        for (k, v) in changed.iter() {
+            if new.insert(k.clone(), v.clone())?.is_none() {
+                return Err(self.err_inconsistent_diff());
+            }
+        }
+        for (k, v) in added.iter() {
+            if new.insert(k.clone(), v.clone())?.is_some() {
+                return Err(self.err_inconsistent_diff());
+            }
+        }
+        Ok(new)
+    }
+
+    // Notes on metering: covered by the metered vector operations.
+    pub(crate) fn vec_apply_diff(
+        &self,
+        base: &HostVec,
+        added: &HostMap,
+        removed: &HostVec,
+        changed: &HostMap,
+    ) -> Result<HostVec, HostError> {
+        // Removed indices must be exactly the tail of `base`, and added ones
+        // must directly follow it.
+        let new_len = base
+            .len()
+            .checked_sub(removed.len())
+            .ok_or_else(|| self.err_inconsistent_diff())?;
+        for (i, k) in removed.iter().enumerate() {
+            if self.index_from_key(k)? != new_len + i {
+                return Err(self.err_inconsistent_diff());
+            }
+        }
+        if !removed.is_empty() && !added.is_empty() {
+            return Err(self.err_inconsistent_diff());
+        }
+        let mut new = base.metered_clone(&self.0.budget)?;
+        for _ in 0..removed.len() {
+            new.pop_back()?;
+        }
+        for (k, v) in changed.iter() {
+            let i = self.index_from_key(k)?;
+            if i >= new_len {
+                return Err(self.err_inconsistent_diff());
+            }
+            new.set(i, v.clone())?;
+        }
+        for (i, (k, v)) in added.iter().enumerate() {
+            if self.index_from_key(k)? != base.len() + i {
+                return Err(self.err_inconsistent_diff());
+            }
+            new.push_back(v.clone())?;
+        }
+        Ok(new)
+    }
+}
@@ -1,6 +1,8 @@
 use crate::{
+    budget::TYPE_LIMIT_EXCEEDED,
     events::{Events, HostEvent},
-    xdr::{self, ScStatus},
+    host::MAX_FRAME_DEPTH_EXCEEDED,
+    xdr::{self, ScStatus, ScStatusType, ScVmErrorCode},
     Status,
 };
 use backtrace::{Backtrace, BacktraceFrame};
@@ -90,6 +92,24 @@ impl Debug for HostError {
 }
 
 impl HostError {
+    /// Returns whether a caller can carry on after this error. Running out of
+    /// budget, overall or for a single cost type, overflowing the wasm stack
+    /// or exceeding the maximum frame depth leaves nothing to carry on with,
+    /// so `try_call` re-raises such errors rather than returning their status.
+    pub fn is_recoverable(&self) -> bool {
+        if self.status == MAX_FRAME_DEPTH_EXCEEDED {
+            return false;
+        }
+        if !self.status.is_type(ScStatusType::VmError) {
+            return true;
+        }
+        let code = self.status.get_code();
+        code != ScVmErrorCode::TrapMemLimitExceeded as u32
+            && code != ScVmErrorCode::TrapCpuLimitExceeded as u32
+            && code != ScVmErrorCode::TrapStackOverflow as u32
+            && code != TYPE_LIMIT_EXCEEDED.get_code()
+    }
+
     #[cfg(test)]
     pub fn result_matches_err_status<T, C>(res: Result<T, HostError>, code: C) -> bool
     where
@@ -103,6 +123,28 @@ impl HostError {
             }
         }
     }
+
+    /// Returns whether `res` is an error whose most recent debug event has the
+    /// message `msg`, for telling apart failures that share a status.
+    #[cfg(test)]
+    pub fn result_matches_debug_msg<T>(res: Result<T, HostError>, msg: &str) -> bool {
+        match res {
+            Ok(_) => false,
+            Err(he) => he.debug_msgs().last() == Some(&msg),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn debug_msgs(&self) -> Vec<&'static str> {
+        self.events
+            .iter()
+            .flat_map(|evs| evs.0.iter())
+            .filter_map(|ev| match ev {
+                HostEvent::Debug(de) => de.msg,
+                HostEvent::Contract(_) => None,
+            })
+            .collect()
+    }
 }
 
 impl<T> From<T> for HostError
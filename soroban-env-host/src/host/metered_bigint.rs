@@ -115,6 +115,18 @@ impl MeteredBigInt {
         self.num.to_i64()
     }
 
+    pub(crate) fn from_i128(budget: Budget, x: i128) -> Result<Self, HostError> {
+        budget.charge(CostType::BigIntNew, 1)?;
+        Ok(Self {
+            budget,
+            num: Into::<BigInt>::into(x),
+        })
+    }
+
+    pub(crate) fn to_i128(&self) -> Option<i128> {
+        self.num.to_i128()
+    }
+
     pub(crate) fn add(&self, other: &Self) -> Result<Self, HostError> {
         self.charge_add_sub(cmp::max(self.bits(), other.bits()))?;
         Ok(Self {
@@ -139,6 +151,15 @@ impl MeteredBigInt {
         })
     }
 
+    pub(crate) fn mul_u32(&self, scalar: u32) -> Result<Self, HostError> {
+        // A single-limb multiplier is a linear pass over the u64 words of `self`.
+        self.charge_mul((self.bits() + 63) / 64)?;
+        Ok(Self {
+            budget: self.budget.clone(),
+            num: (&self.num).mul(scalar),
+        })
+    }
+
     pub(crate) fn div(&self, other: &Self) -> Result<Self, HostError> {
         assert!(!other.is_zero());
         self.charge_div_rem(cmp::max(self.bits(), other.bits()))?;
@@ -215,6 +236,11 @@ impl MeteredBigInt {
         }
     }
 
+    pub(crate) fn metered_cmp(&self, other: &Self) -> Result<cmp::Ordering, HostError> {
+        self.charge_cmp(cmp::max(self.bits(), other.bits()))?;
+        Ok(self.num.cmp(&other.num))
+    }
+
     pub(crate) fn is_zero(&self) -> bool {
         self.num.is_zero()
     }
@@ -2,9 +2,9 @@ use crate::{
     budget::{Budget, CostType},
     HostError,
 };
-use im_rc::ordmap::{ConsumingIter, Iter, Keys, Values};
+use im_rc::ordmap::{ConsumingIter, Iter, Keys, RangedIter, Values};
 use im_rc::OrdMap;
-use std::{borrow::Borrow, rc::Rc};
+use std::{borrow::Borrow, ops::RangeBounds, rc::Rc};
 
 pub struct MeteredOrdMap<K, V> {
     pub(crate) budget: Budget,
@@ -152,6 +152,19 @@ where
     pub fn iter(&self) -> Iter<'_, K, V> {
         self.map.iter()
     }
+
+    /// Iterates over the entries whose keys fall in `range`, in key order.
+    /// Like [MeteredOrdMap::iter], this is not charged; callers charge for
+    /// the entries they actually visit.
+    #[inline]
+    pub fn range<R, BK>(&self, range: R) -> RangedIter<'_, K, V>
+    where
+        R: RangeBounds<BK>,
+        BK: Ord + ?Sized,
+        K: Borrow<BK>,
+    {
+        self.map.range(range)
+    }
 }
 
 impl<K, V> Clone for MeteredOrdMap<K, V> {
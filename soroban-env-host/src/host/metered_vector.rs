@@ -122,6 +122,42 @@ impl<A: Clone> MeteredVector<A> {
         })
     }
 
+    // Time: O(n)
+    pub fn reverse(&mut self) -> Result<(), HostError> {
+        self.charge_mut_access(self.len() as u64)?;
+        self.vec = self.vec.iter().rev().cloned().collect();
+        Ok(())
+    }
+
+    // Time: O(n log n). Charged one `HostVecCmp` per comparison a merge sort
+    // may make.
+    pub fn sort(&mut self) -> Result<(), HostError>
+    where
+        A: Ord,
+    {
+        let n = self.len() as u64;
+        let log_n = (u64::BITS - n.leading_zeros()) as u64;
+        self.budget.charge(CostType::HostVecCmp, n * log_n)?;
+        // `Vector::sort` is not stable, so sort a flat copy instead.
+        let mut elts: Vec<A> = self.vec.iter().cloned().collect();
+        elts.sort();
+        self.vec = elts.into();
+        Ok(())
+    }
+
+    // Time: O(n). Charged one `HostVecCmp` per element.
+    pub fn dedup(&mut self) -> Result<(), HostError>
+    where
+        A: PartialEq,
+    {
+        self.budget
+            .charge(CostType::HostVecCmp, self.len() as u64)?;
+        let mut elts: Vec<A> = self.vec.iter().cloned().collect();
+        elts.dedup();
+        self.vec = elts.into();
+        Ok(())
+    }
+
     /// Time: O(1)
     #[inline]
     pub fn iter(&self) -> Iter<'_, A> {
@@ -1,8 +1,11 @@
 use std::ops::Range;
 
+use crate::budget::CostType;
 use crate::events::DebugError;
-use crate::xdr::{ScHostFnErrorCode, ScHostObjErrorCode};
-use crate::{Host, HostError, RawVal};
+use crate::host::MAX_SCVAL_BINARY_LEN;
+use crate::host_object::HostVal;
+use crate::xdr::{ScContractCode, ScHostFnErrorCode, ScHostObjErrorCode, ScObject, ScVal};
+use crate::{Host, HostError, RawVal, Symbol};
 
 impl Host {
     // Notes on metering: free
@@ -80,4 +83,65 @@ impl Host {
         })?;
         self.valid_range_from_start_end_bound(start, end, bound)
     }
+
+    /// Checks the structure of `v` and of every value nested in it: symbols
+    /// must be well-formed (`SymbolTooLong` or `SymbolBadChar` otherwise), map
+    /// keys strictly increasing (`InputArgsInvalid`), and binaries, including
+    /// wasm code, at most [`MAX_SCVAL_BINARY_LEN`] bytes long
+    /// (`InputArgsWrongLength`). Every value written by `put_contract_data`
+    /// is checked.
+    // Notes on metering: charges `ValXdrConv` once per value visited, plus the
+    // conversion and comparison of map keys as host values.
+    pub fn validate_scval(&self, v: &ScVal) -> Result<(), HostError> {
+        self.charge_budget(CostType::ValXdrConv, 1)?;
+        match v {
+            ScVal::Symbol(s) => {
+                Symbol::try_from(s).map_err(|e| self.err_status(e))?;
+                Ok(())
+            }
+            ScVal::Object(Some(ob)) => self.validate_scobject(ob),
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_scobject(&self, ob: &ScObject) -> Result<(), HostError> {
+        match ob {
+            ScObject::Vec(v) => v.0.iter().try_for_each(|e| self.validate_scval(e)),
+            ScObject::Map(m) => {
+                m.0.iter().try_for_each(|e| {
+                    self.validate_scval(&e.key)?;
+                    self.validate_scval(&e.val)
+                })?;
+                // Keys must follow the host's order of values, which is not
+                // the derived order of their XDR (e.g. for BigInts), so they
+                // are compared as host values.
+                let keys =
+                    m.0.iter()
+                        .map(|e| self.to_host_val(&e.key))
+                        .collect::<Result<Vec<HostVal>, HostError>>()?;
+                if keys.windows(2).any(|w| w[0] >= w[1]) {
+                    return Err(self.err_status_msg(
+                        ScHostFnErrorCode::InputArgsInvalid,
+                        "map keys are not strictly increasing",
+                    ));
+                }
+                Ok(())
+            }
+            ScObject::Bytes(b) => self.validate_binary_len(b.as_slice().len()),
+            ScObject::ContractCode(ScContractCode::Wasm(b)) => {
+                self.validate_binary_len(b.as_slice().len())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_binary_len(&self, len: usize) -> Result<(), HostError> {
+        if len > MAX_SCVAL_BINARY_LEN {
+            return Err(self.err_status_msg(
+                ScHostFnErrorCode::InputArgsWrongLength,
+                "binary is too long",
+            ));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,234 @@
+//! Works out the shape of XDR-encoded [ScVal]s without building them: the
+//! length of an encoding, so serialization can be charged before any bytes are
+//! written, and the nesting depth of an encoding, so deeply nested input can be
+//! rejected before it is parsed.
+
+use crate::budget::CostType;
+use crate::host_object::HostObject;
+use crate::xdr::{
+    ScBigInt, ScContractCode, ScHostObjErrorCode, ScObject, ScObjectType, ScStatus, ScStatusType,
+    ScVal, ScValType,
+};
+use crate::{Host, HostError, Object, RawVal};
+
+/// The deepest nesting of [ScVal]s, counting the outermost one, accepted by
+/// [check_scval_xdr_depth].
+pub(crate) const MAX_SCVAL_XDR_DEPTH: u32 = 64;
+
+// Discriminants, enums and 32-bit integers.
+const WORD: u64 = 4;
+// 64-bit integers.
+const DWORD: u64 = 8;
+// Hashes and ed25519 public keys.
+const HASH: u64 = 32;
+
+// A variable-length opaque or string: a length word, then the bytes padded to
+// a multiple of 4.
+fn var_opaque_len(n: usize) -> u64 {
+    WORD + ((n as u64 + 3) & !3)
+}
+
+pub(crate) fn scval_xdr_len(v: &ScVal) -> u64 {
+    WORD + match v {
+        ScVal::U63(_) | ScVal::Bitset(_) => DWORD,
+        ScVal::U32(_) | ScVal::I32(_) | ScVal::Static(_) => WORD,
+        // An optional: a presence word, then the value if present.
+        ScVal::Object(None) => WORD,
+        ScVal::Object(Some(ob)) => WORD + scobject_xdr_len(ob),
+        ScVal::Symbol(s) => var_opaque_len(s.as_slice().len()),
+        ScVal::Status(st) => match st {
+            ScStatus::Ok => WORD,
+            _ => WORD + WORD,
+        },
+    }
+}
+
+fn scobject_xdr_len(ob: &ScObject) -> u64 {
+    WORD + match ob {
+        ScObject::Vec(v) => WORD + v.0.iter().map(scval_xdr_len).sum::<u64>(),
+        ScObject::Map(m) => {
+            WORD + m
+                .0
+                .iter()
+                .map(|e| scval_xdr_len(&e.key) + scval_xdr_len(&e.val))
+                .sum::<u64>()
+        }
+        ScObject::U64(_) | ScObject::I64(_) => DWORD,
+        ScObject::Bytes(b) => var_opaque_len(b.as_slice().len()),
+        ScObject::BigInt(bi) => match bi {
+            ScBigInt::Zero => WORD,
+            ScBigInt::Positive(b) | ScBigInt::Negative(b) => {
+                WORD + var_opaque_len(b.as_slice().len())
+            }
+        },
+        // Both are single-armed unions over a 32-byte value.
+        ScObject::Hash(_) | ScObject::PublicKey(_) => WORD + HASH,
+        ScObject::ContractCode(cc) => match cc {
+            ScContractCode::Wasm(b) => WORD + var_opaque_len(b.as_slice().len()),
+            ScContractCode::Token => WORD,
+        },
+    }
+}
+
+impl Host {
+    /// Works out the length of the XDR encoding of the [ScVal] that `v`
+    /// converts to, as [scval_xdr_len] would, but by walking the host objects
+    /// `v` refers to rather than converting them.
+    // Notes on metering: each object is charged a `VisitObject`, and each vec
+    // or map an `ImVecImmutEntry` or `ImMapImmutEntry` on its length.
+    pub(crate) fn rawval_xdr_len(&self, v: RawVal) -> Result<u64, HostError> {
+        if !v.is::<Object>() {
+            return Ok(scval_xdr_len(&self.from_host_val(v)?));
+        }
+        let body = unsafe {
+            self.unchecked_visit_val_obj(v, |hopt| match hopt {
+                None => Err(self.err_status(ScHostObjErrorCode::UnknownReference)),
+                Some(hobj) => self.host_object_xdr_len(hobj),
+            })?
+        };
+        // The value's type, the optional's presence word, the object's type.
+        Ok(WORD + WORD + WORD + body)
+    }
+
+    // Mirrors `scobject_xdr_len`, less the object's type.
+    fn host_object_xdr_len(&self, hobj: &HostObject) -> Result<u64, HostError> {
+        Ok(match hobj {
+            HostObject::Vec(v) => {
+                self.charge_budget(CostType::ImVecImmutEntry, v.len() as u64)?;
+                let mut len = WORD;
+                for e in v.iter() {
+                    len += self.rawval_xdr_len(e.val)?;
+                }
+                len
+            }
+            HostObject::Map(m) => {
+                self.charge_budget(CostType::ImMapImmutEntry, m.len() as u64)?;
+                let mut len = WORD;
+                for (k, v) in m.iter() {
+                    len += self.rawval_xdr_len(k.val)? + self.rawval_xdr_len(v.val)?;
+                }
+                len
+            }
+            HostObject::U64(_) | HostObject::I64(_) => DWORD,
+            HostObject::Bin(b) => var_opaque_len(b.len()),
+            HostObject::BigInt(bi) if bi.is_zero() => WORD,
+            HostObject::BigInt(bi) => WORD + var_opaque_len(((bi.bits() + 7) / 8) as usize),
+            HostObject::Hash(_) | HostObject::PublicKey(_) => WORD + HASH,
+            HostObject::ContractCode(ScContractCode::Wasm(b)) => {
+                WORD + var_opaque_len(b.as_slice().len())
+            }
+            HostObject::ContractCode(ScContractCode::Token) => WORD,
+        })
+    }
+}
+
+pub(crate) enum XdrScanError {
+    /// The input is not a well-formed encoding.
+    Malformed,
+    /// The input nests deeper than allowed.
+    TooDeep,
+}
+
+struct Cursor<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn word(&mut self) -> Result<u32, XdrScanError> {
+        let (w, rest) = self.split(WORD)?;
+        self.buf = rest;
+        Ok(u32::from_be_bytes([w[0], w[1], w[2], w[3]]))
+    }
+
+    fn skip(&mut self, n: u64) -> Result<(), XdrScanError> {
+        self.buf = self.split(n)?.1;
+        Ok(())
+    }
+
+    fn skip_var_opaque(&mut self) -> Result<(), XdrScanError> {
+        let n = self.word()?;
+        self.skip((n as u64 + 3) & !3)
+    }
+
+    fn split(&self, n: u64) -> Result<(&'a [u8], &'a [u8]), XdrScanError> {
+        if (self.buf.len() as u64) < n {
+            return Err(XdrScanError::Malformed);
+        }
+        Ok(self.buf.split_at(n as usize))
+    }
+}
+
+/// Walks the XDR encoding of an [ScVal] in `buf`, failing if it nests deeper
+/// than `max_depth` or ends early. Nothing is allocated, and recursion is
+/// bounded by `max_depth`, so this is safe to run on untrusted input before
+/// handing it to `read_xdr`.
+pub(crate) fn check_scval_xdr_depth(buf: &[u8], max_depth: u32) -> Result<(), XdrScanError> {
+    scan_scval(&mut Cursor { buf }, max_depth)
+}
+
+fn scan_scval(c: &mut Cursor, depth_left: u32) -> Result<(), XdrScanError> {
+    if depth_left == 0 {
+        return Err(XdrScanError::TooDeep);
+    }
+    let ty = c.word()?;
+    if ty == ScValType::U63 as u32 || ty == ScValType::Bitset as u32 {
+        c.skip(DWORD)
+    } else if ty == ScValType::U32 as u32
+        || ty == ScValType::I32 as u32
+        || ty == ScValType::Static as u32
+    {
+        c.skip(WORD)
+    } else if ty == ScValType::Object as u32 {
+        match c.word()? {
+            0 => Ok(()),
+            1 => scan_scobject(c, depth_left),
+            _ => Err(XdrScanError::Malformed),
+        }
+    } else if ty == ScValType::Symbol as u32 {
+        c.skip_var_opaque()
+    } else if ty == ScValType::Status as u32 {
+        if c.word()? == ScStatusType::Ok as u32 {
+            Ok(())
+        } else {
+            c.skip(WORD)
+        }
+    } else {
+        Err(XdrScanError::Malformed)
+    }
+}
+
+fn scan_scobject(c: &mut Cursor, depth_left: u32) -> Result<(), XdrScanError> {
+    let ty = c.word()?;
+    if ty == ScObjectType::Vec as u32 {
+        for _ in 0..c.word()? {
+            scan_scval(c, depth_left - 1)?;
+        }
+        Ok(())
+    } else if ty == ScObjectType::Map as u32 {
+        for _ in 0..c.word()? {
+            scan_scval(c, depth_left - 1)?;
+            scan_scval(c, depth_left - 1)?;
+        }
+        Ok(())
+    } else if ty == ScObjectType::U64 as u32 || ty == ScObjectType::I64 as u32 {
+        c.skip(DWORD)
+    } else if ty == ScObjectType::Bytes as u32 {
+        c.skip_var_opaque()
+    } else if ty == ScObjectType::BigInt as u32 {
+        // A zero sign has no magnitude.
+        match c.word()? {
+            0 => Ok(()),
+            _ => c.skip_var_opaque(),
+        }
+    } else if ty == ScObjectType::Hash as u32 || ty == ScObjectType::PublicKey as u32 {
+        c.skip(WORD + HASH)
+    } else if ty == ScObjectType::ContractCode as u32 {
+        // Only wasm code has a body.
+        match c.word()? {
+            0 => c.skip_var_opaque(),
+            _ => Ok(()),
+        }
+    } else {
+        Err(XdrScanError::Malformed)
+    }
+}
@@ -25,6 +25,22 @@ pub(crate) enum HostObject {
     ContractCode(xdr::ScContractCode),
 }
 
+impl HostObject {
+    pub(crate) fn get_type(&self) -> ScObjectType {
+        match self {
+            HostObject::Vec(_) => ScObjectType::Vec,
+            HostObject::Map(_) => ScObjectType::Map,
+            HostObject::U64(_) => ScObjectType::U64,
+            HostObject::I64(_) => ScObjectType::I64,
+            HostObject::Bin(_) => ScObjectType::Bytes,
+            HostObject::BigInt(_) => ScObjectType::BigInt,
+            HostObject::Hash(_) => ScObjectType::Hash,
+            HostObject::PublicKey(_) => ScObjectType::PublicKey,
+            HostObject::ContractCode(_) => ScObjectType::ContractCode,
+        }
+    }
+}
+
 pub(crate) trait HostObjectType: Sized {
     fn get_type() -> ScObjectType;
     fn inject(self) -> HostObject;
@@ -48,6 +48,6 @@ mod test;
 pub use host::ContractFunctionSet;
 #[cfg(feature = "testutils")]
 pub use host::LedgerInfo;
-pub use host::{Host, HostError};
+pub use host::{FrameInfo, Host, HostError};
 pub use im_rc;
 pub use soroban_env_common::*;
@@ -1,5 +1,5 @@
-mod base_types;
-mod token;
+pub(crate) mod base_types;
+pub(crate) mod token;
 
 use crate::host::{Host, HostError};
 use soroban_env_common::{RawVal, Symbol};
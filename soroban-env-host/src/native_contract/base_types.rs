@@ -68,6 +68,24 @@ impl core::ops::Sub for BigInt {
     }
 }
 
+impl core::ops::Mul for BigInt {
+    type Output = Result<BigInt, HostError>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let res = self.0.env.bigint_mul(self.0.val, rhs.0.val)?;
+        BigInt::try_from_val(&self.0.env, res)
+    }
+}
+
+impl core::ops::Div for BigInt {
+    type Output = Result<BigInt, HostError>;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        let res = self.0.env.bigint_div(self.0.val, rhs.0.val)?;
+        BigInt::try_from_val(&self.0.env, res)
+    }
+}
+
 impl BigInt {
     pub fn from_u64(env: &Host, x: u64) -> Result<Self, HostError> {
         let res = env.bigint_from_u64(x)?;
@@ -1,12 +1,12 @@
 mod admin;
 mod allowance;
-mod balance;
+pub(crate) mod balance;
 mod contract;
 mod cryptography;
 mod error;
-mod metadata;
+pub(crate) mod metadata;
 mod nonce;
-mod public_types;
+pub(crate) mod public_types;
 mod storage_types;
 
 pub use contract::Token;
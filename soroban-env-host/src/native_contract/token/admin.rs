@@ -14,7 +14,7 @@ pub fn has_administrator(e: &Host) -> Result<bool, Error> {
     Ok(rv.try_into()?)
 }
 
-fn read_administrator(e: &Host) -> Result<Identifier, Error> {
+pub fn read_administrator(e: &Host) -> Result<Identifier, Error> {
     let key = DataKey::Admin;
     let rv = e.get_contract_data(key.try_into_val(e)?)?;
     Ok(Identifier::try_from_val(e, rv)?)
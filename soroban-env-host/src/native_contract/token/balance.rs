@@ -1,18 +1,58 @@
 use crate::host::Host;
-use crate::native_contract::base_types::BigInt;
+use crate::native_contract::base_types::{BigInt, Vec};
+use crate::native_contract::token::admin::read_administrator;
 use crate::native_contract::token::error::Error;
+use crate::native_contract::token::metadata::read_fee_bps;
 use crate::native_contract::token::public_types::Identifier;
 use crate::native_contract::token::storage_types::DataKey;
 use core::cmp::Ordering;
-use soroban_env_common::{CheckedEnv, TryIntoVal};
+use soroban_env_common::{CheckedEnv, Object, RawVal, Symbol, TryFromVal, TryIntoVal};
 
 pub fn read_balance(e: &Host, id: Identifier) -> Result<BigInt, Error> {
     let key = DataKey::Balance(id);
-    if let Ok(balance) = e.get_contract_data(key.try_into_val(e)?) {
-        Ok(balance.try_into_val(e)?)
-    } else {
+    let rv = e.try_get_contract_data(key.try_into_val(e)?)?;
+    if rv.is_void() {
         Ok(BigInt::from_u64(e, 0)?)
+    } else {
+        Ok(rv.try_into_val(e)?)
+    }
+}
+
+// Reads the balances of the identifiers in `ids` in order, with zero for any
+// that have none.
+pub fn read_balances(e: &Host, ids: Vec) -> Result<Vec, Error> {
+    let mut balances = Vec::new(e)?;
+    for i in 0..ids.len()? {
+        let id: Identifier = ids.get(i)?;
+        balances.push(read_balance(e, id)?)?;
     }
+    Ok(balances)
+}
+
+// Returns a vec of `[id, balance]` pairs, in key order, for every holder with a
+// nonzero balance. Only balances in the storage map (the footprint, when
+// enforcing) are visible. The key scan is charged per entry by
+// `get_contract_data_keys`, and each balance read by storage.
+pub fn read_holders(e: &Host) -> Result<Vec, Error> {
+    let keys = e.get_contract_data_keys()?;
+    let zero = BigInt::from_u64(e, 0)?;
+    let mut holders = Vec::new(e)?;
+    for i in 0..u32::try_from(e.vec_len(keys)?)? {
+        let key: RawVal = e.vec_get(keys, i.into())?;
+        // Skip the contract code entry and any other non-balance keys.
+        let id = match DataKey::try_from_val(e, key) {
+            Ok(DataKey::Balance(id)) => id,
+            _ => continue,
+        };
+        let balance = read_balance(e, id.clone())?;
+        if balance.compare(&zero)? != Ordering::Equal {
+            let mut pair = Vec::new(e)?;
+            pair.push(id)?;
+            pair.push(balance)?;
+            holders.push(pair)?;
+        }
+    }
+    Ok(holders)
 }
 
 fn write_balance(e: &Host, id: Identifier, amount: BigInt) -> Result<(), Error> {
@@ -43,10 +83,68 @@ pub fn spend_balance(e: &Host, id: Identifier, amount: BigInt) -> Result<(), Err
     }
 }
 
+// Returns the fee withheld from a transfer of `amount`, rounded down. The fee
+// is configured in basis points in the token metadata and defaults to zero.
+pub fn compute_fee(e: &Host, amount: BigInt) -> Result<BigInt, Error> {
+    let fee_bps = read_fee_bps(e)?;
+    if fee_bps == 0 {
+        return Ok(BigInt::from_u64(e, 0)?);
+    }
+    let scaled = (amount * BigInt::from_u64(e, fee_bps.into())?)?;
+    Ok((scaled / BigInt::from_u64(e, 10_000)?)?)
+}
+
+// Moves `amount` from `from` to `to`, withholding the transfer fee from what
+// `to` receives and crediting it to the administrator. Every check is made
+// before any balance is written, and a single "transfer" event is emitted with
+// `[from, to, received, fee]` as data, which add up to `amount`.
+pub fn transfer_balance(
+    e: &Host,
+    from: Identifier,
+    to: Identifier,
+    amount: BigInt,
+) -> Result<(), Error> {
+    let from_balance = read_balance(e, from.clone())?;
+    if read_state(e, from.clone())? || read_state(e, to.clone())? {
+        return Err(Error::ContractError);
+    }
+    if from_balance.compare(&amount)? == Ordering::Less {
+        return Err(Error::ContractError);
+    }
+    let fee = compute_fee(e, amount.clone())?;
+    let received = (amount.clone() - fee.clone())?;
+
+    let zero = BigInt::from_u64(e, 0)?;
+    let fee_to = if fee.compare(&zero)? == Ordering::Equal {
+        None
+    } else {
+        Some(read_administrator(e)?)
+    };
+
+    write_balance(e, from.clone(), (from_balance - amount)?)?;
+    // Read each later balance only after the writes before it, so that a
+    // transfer to oneself or involving the administrator sees every change.
+    let to_balance = read_balance(e, to.clone())?;
+    write_balance(e, to.clone(), (to_balance + received.clone())?)?;
+    if let Some(admin) = fee_to {
+        let admin_balance = read_balance(e, admin.clone())?;
+        write_balance(e, admin, (admin_balance + fee.clone())?)?;
+    }
+
+    let topics = e.vec_push(e.vec_new(().into())?, Symbol::from_str("transfer").into())?;
+    let mut data = Vec::new(e)?;
+    data.push(from)?;
+    data.push(to)?;
+    data.push(received)?;
+    data.push(fee)?;
+    e.contract_event(topics, Object::from(data).to_raw())?;
+    Ok(())
+}
+
 pub fn read_state(e: &Host, id: Identifier) -> Result<bool, Error> {
     let key = DataKey::State(id);
     if let Ok(state) = e.get_contract_data(key.try_into_val(e)?) {
-        Ok(state.try_into()?)
+        Ok(e.bool_from_rawval_input("state", state)?)
     } else {
         Ok(false)
     }
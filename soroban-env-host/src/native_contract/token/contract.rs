@@ -5,12 +5,14 @@ use crate::native_contract::token::admin::{
 };
 use crate::native_contract::token::allowance::{read_allowance, spend_allowance, write_allowance};
 use crate::native_contract::token::balance::{
-    read_balance, read_state, receive_balance, spend_balance, write_state,
+    read_balance, read_balances, read_holders, read_state, receive_balance, spend_balance,
+    transfer_balance, write_state,
 };
 use crate::native_contract::token::cryptography::{check_auth, Domain};
 use crate::native_contract::token::error::Error;
 use crate::native_contract::token::metadata::{
-    read_decimal, read_name, read_symbol, write_decimal, write_name, write_symbol,
+    read_decimal, read_fee_bps, read_name, read_symbol, write_decimal, write_fee_bps, write_name,
+    write_symbol,
 };
 use crate::native_contract::token::nonce::read_nonce;
 use crate::native_contract::token::public_types::{Authorization, Identifier, KeyedAuthorization};
@@ -39,8 +41,12 @@ pub trait TokenTrait {
 
     fn balance(e: &Host, id: Identifier) -> Result<BigInt, Error>;
 
+    fn balances(e: &Host, ids: Vec) -> Result<Vec, Error>;
+
     fn is_frozen(e: &Host, id: Identifier) -> Result<bool, Error>;
 
+    fn holders(e: &Host) -> Result<Vec, Error>;
+
     fn xfer(
         e: &Host,
         from: KeyedAuthorization,
@@ -66,6 +72,10 @@ pub trait TokenTrait {
 
     fn unfreeze(e: &Host, admin: Authorization, id: Identifier) -> Result<(), Error>;
 
+    fn set_fee(e: &Host, admin: Authorization, fee_bps: u32) -> Result<(), Error>;
+
+    fn fee_bps(e: &Host) -> Result<u32, Error>;
+
     fn decimals(e: &Host) -> Result<u32, Error>;
 
     fn name(e: &Host) -> Result<Bytes, Error>;
@@ -122,10 +132,18 @@ impl TokenTrait for Token {
         read_balance(e, id)
     }
 
+    fn balances(e: &Host, ids: Vec) -> Result<Vec, Error> {
+        read_balances(e, ids)
+    }
+
     fn is_frozen(e: &Host, id: Identifier) -> Result<bool, Error> {
         read_state(&e, id)
     }
 
+    fn holders(e: &Host) -> Result<Vec, Error> {
+        read_holders(e)
+    }
+
     fn xfer(
         e: &Host,
         from: KeyedAuthorization,
@@ -137,8 +155,7 @@ impl TokenTrait for Token {
         args.push(to.clone())?;
         args.push(amount.clone())?;
         check_auth(&e, from, Domain::Transfer, args)?;
-        spend_balance(&e, from_id, amount.clone())?;
-        receive_balance(&e, to, amount)?;
+        transfer_balance(&e, from_id, to, amount)?;
         Ok(())
     }
 
@@ -156,8 +173,7 @@ impl TokenTrait for Token {
         args.push(amount.clone())?;
         check_auth(&e, spender, Domain::TransferFrom, args)?;
         spend_allowance(&e, from.clone(), spender_id, amount.clone())?;
-        spend_balance(&e, from, amount.clone())?;
-        receive_balance(&e, to, amount)?;
+        transfer_balance(&e, from, to, amount)?;
         Ok(())
     }
 
@@ -208,6 +224,19 @@ impl TokenTrait for Token {
         Ok(())
     }
 
+    fn set_fee(e: &Host, admin: Authorization, fee_bps: u32) -> Result<(), Error> {
+        let auth = to_administrator_authorization(&e, admin)?;
+        let mut args = Vec::new(e)?;
+        args.push(fee_bps)?;
+        check_auth(&e, auth, Domain::SetFee, args)?;
+        write_fee_bps(&e, fee_bps)?;
+        Ok(())
+    }
+
+    fn fee_bps(e: &Host) -> Result<u32, Error> {
+        read_fee_bps(&e)
+    }
+
     fn decimals(e: &Host) -> Result<u32, Error> {
         read_decimal(&e)
     }
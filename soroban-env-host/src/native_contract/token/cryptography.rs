@@ -19,6 +19,7 @@ pub enum Domain {
     Mint = 5,
     SetAdministrator = 6,
     Unfreeze = 7,
+    SetFee = 8,
 }
 
 fn check_ed25519_auth(
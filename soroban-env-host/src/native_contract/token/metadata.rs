@@ -6,8 +6,7 @@ use soroban_env_common::{CheckedEnv, TryIntoVal};
 
 pub fn read_decimal(e: &Host) -> Result<u32, Error> {
     let key = DataKey::Decimals;
-    let rv = e.get_contract_data(key.try_into_val(e)?)?;
-    Ok(rv.try_into()?)
+    Ok(e.get_contract_data_as(key.try_into_val(e)?)?)
 }
 
 pub fn write_decimal(e: &Host, d: u8) -> Result<(), Error> {
@@ -18,24 +17,41 @@ pub fn write_decimal(e: &Host, d: u8) -> Result<(), Error> {
 
 pub fn read_name(e: &Host) -> Result<Bytes, Error> {
     let key = DataKey::Name;
-    let rv = e.get_contract_data(key.try_into_val(e)?)?;
-    Ok(rv.try_into_val(e)?)
+    Ok(e.get_contract_data_as(key.try_into_val(e)?)?)
 }
 
 pub fn write_name(e: &Host, d: Bytes) -> Result<(), Error> {
     let key = DataKey::Name;
-    e.put_contract_data(key.try_into_val(e)?, d.try_into_val(e)?)?;
+    e.put_contract_data_from(key.try_into_val(e)?, d)?;
+    Ok(())
+}
+
+pub fn read_fee_bps(e: &Host) -> Result<u32, Error> {
+    let key = DataKey::FeeBps;
+    let rv = e.try_get_contract_data(key.try_into_val(e)?)?;
+    if rv.is_void() {
+        Ok(0)
+    } else {
+        Ok(rv.try_into()?)
+    }
+}
+
+pub fn write_fee_bps(e: &Host, bps: u32) -> Result<(), Error> {
+    if bps > 10_000 {
+        return Err(Error::ContractError);
+    }
+    let key = DataKey::FeeBps;
+    e.put_contract_data(key.try_into_val(e)?, bps.into())?;
     Ok(())
 }
 
 pub fn read_symbol(e: &Host) -> Result<Bytes, Error> {
     let key = DataKey::Symbol;
-    let rv = e.get_contract_data(key.try_into_val(e)?)?;
-    Ok(rv.try_into_val(e)?)
+    Ok(e.get_contract_data_as(key.try_into_val(e)?)?)
 }
 
 pub fn write_symbol(e: &Host, d: Bytes) -> Result<(), Error> {
     let key = DataKey::Symbol;
-    e.put_contract_data(key.try_into_val(e)?, d.try_into_val(e)?)?;
+    e.put_contract_data_from(key.try_into_val(e)?, d)?;
     Ok(())
 }
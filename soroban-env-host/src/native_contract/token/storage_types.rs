@@ -19,4 +19,5 @@ pub enum DataKey {
     Decimals,
     Name,
     Symbol,
+    FeeBps,
 }
@@ -9,7 +9,11 @@
 
 use std::rc::Rc;
 
-use crate::xdr::{LedgerEntry, LedgerKey, ScHostStorageErrorCode};
+use im_rc::{OrdMap, OrdSet};
+
+use crate::xdr::{
+    Hash, LedgerEntry, LedgerKey, LedgerKeyContractData, ScHostStorageErrorCode, ScVal,
+};
 use crate::{host::metered_map::MeteredOrdMap, HostError};
 
 /// A helper type used by [Footprint] to designate which ways
@@ -25,6 +29,18 @@ pub enum AccessType {
     ReadWrite,
 }
 
+/// How the entry for a [LedgerKey] differs between two [Storage] maps, as
+/// reported by [Storage::diff]. Deleted entries count as absent.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StorageDiffKind {
+    /// The entry is only present in the second map.
+    Added,
+    /// The entry is only present in the first map.
+    Removed,
+    /// The entry is present in both maps with different contents.
+    Changed,
+}
+
 /// A helper type used by [FootprintMode::Recording] to provide access
 /// to a stable read-snapshot of a ledger.
 pub trait SnapshotSource {
@@ -110,6 +126,21 @@ pub struct Storage {
     pub footprint: Footprint,
     pub mode: FootprintMode,
     pub map: MeteredOrdMap<LedgerKey, Option<LedgerEntry>>,
+    /// The map as of the last call to [Storage::clear_modified_keys], against
+    /// which [Storage::modified_keys] are found.
+    modified_since: MeteredOrdMap<LedgerKey, Option<LedgerEntry>>,
+    /// Keys passed to [Storage::get] or [Storage::has], and to [Storage::put]
+    /// or [Storage::del], over the lifetime of the [Storage]. See
+    /// [Storage::footprint].
+    read_keys: OrdSet<LedgerKey>,
+    write_keys: OrdSet<LedgerKey>,
+    /// Expiration ledger sequence numbers set by
+    /// [Storage::put_with_expiration]. `ContractDataEntry` has no field for
+    /// them, so they are kept alongside the map rather than in the entries.
+    pub(crate) expirations: OrdMap<LedgerKey, u32>,
+    /// When set, [Storage::put] and [Storage::del] fail while reads still
+    /// work, so a contract can be run without side effects.
+    read_only: bool,
 }
 
 // Notes on metering: all storage operations: `put`, `get`, `del`, `has` are
@@ -125,7 +156,12 @@ impl Storage {
         Self {
             mode: FootprintMode::Enforcing,
             footprint,
+            modified_since: map.clone(),
             map,
+            read_keys: Default::default(),
+            write_keys: Default::default(),
+            expirations: Default::default(),
+            read_only: false,
         }
     }
 
@@ -136,9 +172,31 @@ impl Storage {
             mode: FootprintMode::Recording(src),
             footprint: Footprint::default(),
             map: Default::default(),
+            modified_since: Default::default(),
+            read_keys: Default::default(),
+            write_keys: Default::default(),
+            expirations: Default::default(),
+            read_only: false,
         }
     }
 
+    /// Returns this [Storage] with mutating operations disabled: [Storage::put]
+    /// and [Storage::del] fail while reads still work, so a contract can be
+    /// run without side effects.
+    pub fn into_read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Returns whether mutating operations are disabled.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub(crate) fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only
+    }
+
     /// Attempts to retrieve the [LedgerEntry] associated with a given
     /// [LedgerKey] in the [Storage], returning an error if the key is not
     /// found.
@@ -165,6 +223,7 @@ impl Storage {
                 self.footprint.enforce_access(key, ty)?;
             }
         };
+        self.read_keys.insert(key.clone());
         match self.map.get(key)? {
             None => Err(ScHostStorageErrorCode::MissingKeyInGet.into()),
             Some(None) => Err(ScHostStorageErrorCode::GetOnDeletedKey.into()),
@@ -172,7 +231,15 @@ impl Storage {
         }
     }
 
-    fn put_opt(&mut self, key: &LedgerKey, val: Option<LedgerEntry>) -> Result<(), HostError> {
+    fn put_opt(
+        &mut self,
+        key: &LedgerKey,
+        val: Option<LedgerEntry>,
+        expiration: Option<u32>,
+    ) -> Result<(), HostError> {
+        if self.read_only {
+            return Err(ScHostStorageErrorCode::ReadwriteAccessToReadonlyEntry.into());
+        }
         let ty = AccessType::ReadWrite;
         match self.mode {
             FootprintMode::Recording(_) => {
@@ -183,9 +250,30 @@ impl Storage {
             }
         };
         self.map.insert(key.clone(), val)?;
+        match expiration {
+            Some(seq) => self.expirations.insert(key.clone(), seq),
+            None => self.expirations.remove(key),
+        };
+        self.write_keys.insert(key.clone());
         Ok(())
     }
 
+    /// Returns, in key order, the keys whose entries were changed (put or
+    /// deleted) since the last call to [Storage::clear_modified_keys]. These
+    /// are the [Storage::changes_since] that call, so writes that were rolled
+    /// back, or that left an entry as it was, are not included.
+    pub fn modified_keys(&self) -> Vec<LedgerKey> {
+        self.changes_since(&self.modified_since)
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect()
+    }
+
+    /// Forgets which keys have been changed so far.
+    pub fn clear_modified_keys(&mut self) {
+        self.modified_since = self.map.clone()
+    }
+
     /// Attempts to write to the [LedgerEntry] associated with a given
     /// [LedgerKey] in the [Storage].
     ///
@@ -196,7 +284,25 @@ impl Storage {
     /// [LedgerKey] has been declared in the [Footprint] as
     /// [AccessType::ReadWrite].
     pub fn put(&mut self, key: &LedgerKey, val: &LedgerEntry) -> Result<(), HostError> {
-        self.put_opt(key, Some(val.clone()))
+        self.put_opt(key, Some(val.clone()), None)
+    }
+
+    /// Like [Storage::put], but the entry expires once the ledger sequence
+    /// number exceeds `expire_seq`. A later [Storage::put] or [Storage::del]
+    /// of the key clears the expiration.
+    pub fn put_with_expiration(
+        &mut self,
+        key: &LedgerKey,
+        val: &LedgerEntry,
+        expire_seq: u32,
+    ) -> Result<(), HostError> {
+        self.put_opt(key, Some(val.clone()), Some(expire_seq))
+    }
+
+    /// Returns the expiration ledger sequence number of a key, if it was
+    /// written with [Storage::put_with_expiration].
+    pub fn expiration(&self, key: &LedgerKey) -> Option<u32> {
+        self.expirations.get(key).copied()
     }
 
     /// Attempts to delete the [LedgerEntry] associated with a given [LedgerKey]
@@ -209,7 +315,7 @@ impl Storage {
     /// [LedgerKey] has been declared in the [Footprint] as
     /// [AccessType::ReadWrite].
     pub fn del(&mut self, key: &LedgerKey) -> Result<(), HostError> {
-        self.put_opt(key, None)
+        self.put_opt(key, None, None)
     }
 
     /// Attempts to determine the presence of a [LedgerEntry] associated with a
@@ -226,6 +332,7 @@ impl Storage {
         match self.mode {
             FootprintMode::Recording(ref src) => {
                 self.footprint.record_access(key, ty)?;
+                self.read_keys.insert(key.clone());
                 // We don't cache has() calls but we do
                 // consult the cache before answering them.
                 match self.map.get(key)? {
@@ -236,6 +343,7 @@ impl Storage {
             }
             FootprintMode::Enforcing => {
                 self.footprint.enforce_access(key, ty)?;
+                self.read_keys.insert(key.clone());
                 match self.map.get(key)? {
                     Some(None) => Ok(false),
                     Some(Some(_)) => Ok(true),
@@ -244,6 +352,109 @@ impl Storage {
             }
         }
     }
+
+    /// Returns the keys of all undeleted `ContractData` entries owned by
+    /// `contract_id`, in key order, recording each as read. Since ledger keys
+    /// are ordered by contract id first, this is a range scan over the
+    /// contract's entries and is charged per matching entry. Expired entries
+    /// are included, as expiring depends on the ledger, which [Storage] does
+    /// not know; see [Storage::expiration].
+    ///
+    /// Only entries present in the storage map are visible: in
+    /// [FootprintMode::Enforcing] mode that is the whole footprint, while in
+    /// [FootprintMode::Recording] mode it is only the entries accessed so far,
+    /// as the [SnapshotSource] cannot be scanned.
+    pub fn contract_data_keys(&mut self, contract_id: &Hash) -> Result<Vec<LedgerKey>, HostError> {
+        // `U63(i64::MIN)` is the least `ScVal`, so this is the first possible
+        // key for the contract.
+        let start = LedgerKey::ContractData(LedgerKeyContractData {
+            contract_id: contract_id.clone(),
+            key: ScVal::U63(i64::MIN),
+        });
+        let mut keys = Vec::new();
+        for (k, v) in self.map.range(start..) {
+            match k {
+                LedgerKey::ContractData(LedgerKeyContractData {
+                    contract_id: id, ..
+                }) if id == contract_id => {
+                    self.map.charge_immut_access(1)?;
+                    if v.is_some() {
+                        self.read_keys.insert(k.clone());
+                        keys.push(k.clone());
+                    }
+                }
+                _ => break,
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Returns, in key order, every [LedgerKey] whose entry differs between
+    /// this [Storage]'s map and `other`'s, with how it differs going from
+    /// this map to `other`'s. Only the maps are compared, not footprints or
+    /// expirations.
+    // Notes on metering: charged one `ImMapImmutEntry` on the combined size
+    // of the two maps.
+    pub fn diff(&self, other: &Storage) -> Result<Vec<(LedgerKey, StorageDiffKind)>, HostError> {
+        self.map
+            .charge_immut_access((self.map.len() + other.map.len()) as u64)?;
+        let mut diffs = Vec::new();
+        for (k, v) in self.map.iter() {
+            match (v, other.map.map.get(k).cloned().flatten()) {
+                (Some(_), None) => diffs.push((k.clone(), StorageDiffKind::Removed)),
+                (None, Some(_)) => diffs.push((k.clone(), StorageDiffKind::Added)),
+                (Some(old), Some(new)) if *old != new => {
+                    diffs.push((k.clone(), StorageDiffKind::Changed))
+                }
+                _ => (),
+            }
+        }
+        for (k, v) in other.map.iter() {
+            if v.is_some() && !self.map.map.contains_key(k) {
+                diffs.push((k.clone(), StorageDiffKind::Added));
+            }
+        }
+        diffs.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(diffs)
+    }
+
+    /// Returns, in key order, every written [LedgerKey] whose entry in this
+    /// [Storage]'s map differs from its entry in `baseline`, with the entry
+    /// it now has. `None` means the entry is deleted, so applying the result
+    /// to a ledger holding `baseline` brings it up to date. In
+    /// [FootprintMode::Recording] mode a key absent from `baseline` counts as
+    /// changed once written, since its ledger value may only be known to the
+    /// [SnapshotSource].
+    // Notes on metering: not charged, since this runs after an invocation on
+    // behalf of the embedder.
+    pub fn changes_since(
+        &self,
+        baseline: &MeteredOrdMap<LedgerKey, Option<LedgerEntry>>,
+    ) -> Vec<(LedgerKey, Option<LedgerEntry>)> {
+        self.write_keys
+            .iter()
+            .filter_map(|k| match self.map.map.get(k) {
+                Some(v) if baseline.map.get(k) != Some(v) => Some((k.clone(), v.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the keys accessed over the lifetime of this [Storage] as a
+    /// `(read_only, read_write)` pair of sets, each in key order, suitable for
+    /// building the `LedgerFootprint` of a transaction. A key that was both
+    /// read and written is only in the read-write set. Accesses that were
+    /// later rolled back are still included, since they were still made.
+    pub fn footprint(&self) -> (Vec<LedgerKey>, Vec<LedgerKey>) {
+        let read_only = self
+            .read_keys
+            .iter()
+            .filter(|k| !self.write_keys.contains(k))
+            .cloned()
+            .collect();
+        let read_write = self.write_keys.iter().cloned().collect();
+        (read_only, read_write)
+    }
 }
 
 #[cfg(test)]
@@ -331,9 +542,13 @@ mod test_footprint {
 #[cfg(test)]
 mod test_storage {
     use im_rc::OrdMap;
-    use soroban_env_common::xdr::ScUnknownErrorCode;
+    use soroban_env_common::xdr::{
+        ContractDataEntry, LedgerEntryData, LedgerEntryExt, LedgerKeyContractData,
+        ScUnknownErrorCode, ScVal,
+    };
 
     use super::*;
+    use crate::budget::Budget;
     #[allow(dead_code)]
     struct MockSnapshotSource(OrdMap<LedgerKey, LedgerEntry>);
     #[allow(dead_code)]
@@ -350,4 +565,46 @@ mod test_storage {
             Ok(self.0.contains_key(key))
         }
     }
+
+    fn contract_data_key(k: i32) -> LedgerKey {
+        LedgerKey::ContractData(LedgerKeyContractData {
+            contract_id: [0; 32].into(),
+            key: ScVal::I32(k),
+        })
+    }
+
+    fn contract_data_entry(k: i32) -> LedgerEntry {
+        LedgerEntry {
+            last_modified_ledger_seq: 0,
+            data: LedgerEntryData::ContractData(ContractDataEntry {
+                contract_id: [0; 32].into(),
+                key: ScVal::I32(k),
+                val: ScVal::I32(k),
+            }),
+            ext: LedgerEntryExt::V0,
+        }
+    }
+
+    #[test]
+    fn storage_footprint_splits_reads_and_writes() -> Result<(), HostError> {
+        let budget = Budget::default();
+        let mut fp = Footprint::default();
+        let mut map = OrdMap::new();
+        for k in 1..=4 {
+            fp.record_access(&contract_data_key(k), AccessType::ReadWrite)?;
+            map.insert(contract_data_key(k), Some(contract_data_entry(k)));
+        }
+        let mut storage =
+            Storage::with_enforcing_footprint_and_map(fp, MeteredOrdMap::from_map(budget, map)?);
+        storage.get(&contract_data_key(1))?;
+        storage.has(&contract_data_key(2))?;
+        storage.get(&contract_data_key(3))?;
+        storage.put(&contract_data_key(3), &contract_data_entry(3))?;
+        storage.del(&contract_data_key(4))?;
+
+        let (read_only, read_write) = storage.footprint();
+        assert_eq!(read_only, vec![contract_data_key(1), contract_data_key(2)]);
+        assert_eq!(read_write, vec![contract_data_key(3), contract_data_key(4)]);
+        Ok(())
+    }
 }
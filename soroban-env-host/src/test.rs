@@ -1,10 +1,15 @@
 mod util;
 
+mod account;
 mod basic;
 mod bigint;
 mod binary;
+mod context;
 mod crypto;
+mod ledger;
 mod map;
+mod storage;
+mod token;
 mod vec;
 
 #[cfg(feature = "vm")]
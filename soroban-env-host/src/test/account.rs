@@ -0,0 +1,95 @@
+use crate::{
+    budget::{Budget, CostType},
+    host::metered_map::MeteredOrdMap,
+    im_rc::OrdMap,
+    storage::{AccessType, Footprint, Storage},
+    xdr::{
+        AccountEntry, AccountEntryExt, AccountId, LedgerEntry, LedgerEntryData, LedgerEntryExt,
+        LedgerKey, LedgerKeyAccount, PublicKey, ScHostObjErrorCode, SequenceNumber, Signer,
+        SignerKey, Thresholds, Uint256,
+    },
+    CheckedEnv, Host, HostError, RawVal,
+};
+
+// Builds a host whose storage holds a single account with master key
+// `[0; 32]` and the given ed25519 signer keys, each with weight 1.
+fn host_with_account(signer_keys: &[[u8; 32]]) -> Host {
+    let account_id = AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([0; 32])));
+    let signers: Vec<Signer> = signer_keys
+        .iter()
+        .map(|k| Signer {
+            key: SignerKey::Ed25519(Uint256(*k)),
+            weight: 1,
+        })
+        .collect();
+    let key = LedgerKey::Account(LedgerKeyAccount {
+        account_id: account_id.clone(),
+    });
+    let le = LedgerEntry {
+        last_modified_ledger_seq: 0,
+        data: LedgerEntryData::Account(AccountEntry {
+            account_id,
+            balance: 0,
+            seq_num: SequenceNumber(0),
+            num_sub_entries: 0,
+            inflation_dest: None,
+            flags: 0,
+            home_domain: Default::default(),
+            thresholds: Thresholds([10, 1, 2, 3]),
+            signers: signers.try_into().unwrap(),
+            ext: AccountEntryExt::V0,
+        }),
+        ext: LedgerEntryExt::V0,
+    };
+    let budget = Budget::default();
+    let mut footprint = Footprint::default();
+    footprint.record_access(&key, AccessType::ReadOnly).unwrap();
+    let mut map = OrdMap::default();
+    map.insert(key, Some(le));
+    let storage = Storage::with_enforcing_footprint_and_map(
+        footprint,
+        MeteredOrdMap {
+            budget: budget.clone(),
+            map,
+        },
+    );
+    Host::with_storage_and_budget(storage, budget)
+}
+
+#[test]
+fn signer_weight_scan_is_metered() -> Result<(), HostError> {
+    let keys: Vec<[u8; 32]> = (1..=20).map(|i| [i; 32]).collect();
+    let host = host_with_account(&keys);
+    let a = host.test_bin_obj(&[0; 32])?;
+
+    // Master key: no scan needed.
+    let w = host.account_get_signer_weight(a.to_object(), a.to_object())?;
+    assert_eq!(w.get_payload(), RawVal::from_u32(10).get_payload());
+    host.get_budget(|budget| assert_eq!(budget.get_input(CostType::AccountSignerScan), 0));
+
+    // Unknown signer: the whole signer list is scanned.
+    let s = host.test_bin_obj(&[99; 32])?;
+    let w = host.account_get_signer_weight(a.to_object(), s.to_object())?;
+    assert_eq!(w.get_payload(), RawVal::from_u32(0).get_payload());
+    host.get_budget(|budget| assert_eq!(budget.get_input(CostType::AccountSignerScan), 20));
+    Ok(())
+}
+
+#[test]
+fn enumerate_signers() -> Result<(), HostError> {
+    let keys: Vec<[u8; 32]> = (1..=3).map(|i| [i; 32]).collect();
+    let host = host_with_account(&keys);
+    let a = host.test_bin_obj(&[0; 32])?;
+
+    let n = host.account_get_num_signers(a.to_object())?;
+    assert_eq!(n.get_payload(), RawVal::from_u32(3).get_payload());
+    for (i, k) in keys.iter().enumerate() {
+        let key = host.account_get_signer_key_at(a.to_object(), (i as u32).into())?;
+        let key_ref = host.test_bin_obj(k)?;
+        assert_eq!(host.obj_cmp(key.into(), key_ref.into())?, 0);
+    }
+    let res = host.account_get_signer_key_at(a.to_object(), 3_u32.into());
+    let code = ScHostObjErrorCode::VecIndexOutOfBound;
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
@@ -1,9 +1,10 @@
 use soroban_env_common::{RawVal, TryFromVal};
 
 use crate::{
-    host::HostError,
-    xdr::{ScObjectType, ScVal},
-    Host, IntoVal, Object, RawValConvertible, Tag,
+    budget::CostType,
+    host::{HostError, DEFAULT_MAX_OBJECTS, OBJECT_LIMIT_EXCEEDED},
+    xdr::{ScHostObjErrorCode, ScHostValErrorCode, ScObject, ScObjectType, ScVal, ScVmErrorCode},
+    CheckedEnv, Host, IntoVal, Object, RawValConvertible, Tag,
 };
 
 /// numbers test
@@ -79,3 +80,85 @@ fn tuple_roundtrip() -> Result<(), HostError> {
     assert_eq!(t0, t0_back);
     Ok(())
 }
+
+#[test]
+fn config_fingerprint() -> Result<(), HostError> {
+    let host_a = Host::default();
+    let host_b = Host::default();
+    assert_eq!(host_a.config_fingerprint()?, host_b.config_fingerprint()?);
+
+    let host_c = Host::default().enable_model(CostType::BigIntMul);
+    assert_ne!(host_a.config_fingerprint()?, host_c.config_fingerprint()?);
+    Ok(())
+}
+
+#[test]
+fn dump_objects_in_handle_order() -> Result<(), HostError> {
+    let host = Host::default();
+    host.obj_from_u64(u64::MAX)?;
+    host.test_bin_obj(&[1, 2, 3])?;
+    host.test_vec_obj::<u32>(&[4, 5])?;
+    let expected = vec![
+        ScObject::U64(u64::MAX),
+        host.test_bin_scobj(&[1, 2, 3])?,
+        ScObject::Vec(host.test_scvec::<u32>(&[4, 5])?),
+    ];
+    assert_eq!(host.dump_objects()?, expected);
+    Ok(())
+}
+
+#[test]
+fn bool_roundtrip() -> Result<(), HostError> {
+    let host = Host::default();
+    for b in [true, false] {
+        let v = host.bool_to_rawval(b);
+        assert_eq!(host.bool_from_rawval_input("v", v)?, b);
+    }
+    let res = host.bool_from_rawval_input("v", 1_u32.into());
+    let code = ScHostValErrorCode::UnexpectedValType;
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
+
+#[test]
+fn checked_int_arithmetic() -> Result<(), HostError> {
+    let host = Host::default();
+    let u = |x: u64| host.obj_from_u64(x);
+    let i = |x: i64| host.obj_from_i64(x);
+    assert_eq!(host.obj_to_u64(host.u64_checked_add(u(2)?, u(3)?)?)?, 5);
+    assert_eq!(host.obj_to_u64(host.u64_checked_sub(u(3)?, u(2)?)?)?, 1);
+    assert_eq!(host.obj_to_u64(host.u64_checked_mul(u(3)?, u(4)?)?)?, 12);
+    assert_eq!(host.obj_to_i64(host.i64_checked_add(i(-2)?, i(3)?)?)?, 1);
+    assert_eq!(host.obj_to_i64(host.i64_checked_sub(i(2)?, i(3)?)?)?, -1);
+    assert_eq!(host.obj_to_i64(host.i64_checked_mul(i(-3)?, i(4)?)?)?, -12);
+
+    let code = ScVmErrorCode::TrapIntegerOverflow;
+    let res = host.u64_checked_add(u(u64::MAX)?, u(1)?);
+    assert!(HostError::result_matches_err_status(res, code));
+    let res = host.u64_checked_sub(u(0)?, u(1)?);
+    assert!(HostError::result_matches_err_status(res, code));
+    let res = host.i64_checked_mul(i(i64::MIN)?, i(-1)?);
+    assert!(HostError::result_matches_err_status(res, code));
+
+    // Operands of the wrong object type are rejected.
+    let res = host.u64_checked_add(i(1)?, u(1)?);
+    let code = ScHostObjErrorCode::UnexpectedType;
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
+
+#[test]
+fn object_count_is_bounded() -> Result<(), HostError> {
+    let host = Host::default();
+    assert_eq!(host.get_max_objects(), DEFAULT_MAX_OBJECTS);
+    host.set_max_objects(3);
+    for _ in 0..3 {
+        host.obj_from_u64(1)?;
+    }
+    let res = host.obj_from_u64(1);
+    assert!(HostError::result_matches_err_status(
+        res,
+        OBJECT_LIMIT_EXCEEDED
+    ));
+    Ok(())
+}
@@ -1,4 +1,8 @@
-use crate::{xdr::ScUnknownErrorCode, CheckedEnv, Host, HostError, RawVal};
+use crate::{
+    host::{BIGINT_FOLD_ADD, BIGINT_FOLD_MAX, BIGINT_FOLD_MIN, BIGINT_FOLD_MUL},
+    xdr::{ScHostFnErrorCode, ScHostValErrorCode, ScUnknownErrorCode, ScVmErrorCode},
+    CheckedEnv, Host, HostError, Object, RawVal,
+};
 
 #[test]
 fn bigint_tests() -> Result<(), HostError> {
@@ -213,3 +217,155 @@ fn bigint_tests() -> Result<(), HostError> {
     }
     Ok(())
 }
+
+#[test]
+fn bigint_mul_u32_matches_bigint_mul() -> Result<(), HostError> {
+    let host = Host::default();
+    let big = host.bigint_from_u64(u64::MAX)?;
+    let big = host.bigint_mul(big, big)?;
+    let big = host.bigint_mul(big, big)?;
+    let xs = [
+        host.bigint_from_i64(0)?,
+        host.bigint_from_i64(-438730)?,
+        host.bigint_from_u64(u64::MAX)?,
+        big,
+        host.bigint_neg(big)?,
+    ];
+    for x in xs {
+        for scalar in [0_u32, 1, 7, u32::MAX] {
+            let obj_res = host.bigint_mul_u32(x, scalar.into())?;
+            let obj_ref = host.bigint_mul(x, host.bigint_from_u64(scalar as u64)?)?;
+            assert_eq!(host.obj_cmp(obj_res.into(), obj_ref.into())?, 0);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn bigint_vec_max_bits() -> Result<(), HostError> {
+    let host = Host::default();
+    let mut v = host.vec_new(RawVal::from_void())?;
+    let max_bits = host.bigint_vec_max_bits(v)?;
+    assert_eq!(max_bits.get_payload(), RawVal::from_u32(0).get_payload());
+
+    let zero = host.bigint_from_u64(0)?;
+    v = host.vec_push(v, zero.into())?;
+    v = host.vec_push(v, zero.into())?;
+    let max_bits = host.bigint_vec_max_bits(v)?;
+    assert_eq!(max_bits.get_payload(), RawVal::from_u32(0).get_payload());
+
+    // 1000 needs 10 bits, and the sign of -700 is not counted.
+    v = host.vec_push(v, host.bigint_from_u64(3)?.into())?;
+    v = host.vec_push(v, host.bigint_from_u64(1000)?.into())?;
+    v = host.vec_push(v, host.bigint_from_i64(-700)?.into())?;
+    let max_bits = host.bigint_vec_max_bits(v)?;
+    assert_eq!(max_bits.get_payload(), RawVal::from_u32(10).get_payload());
+    Ok(())
+}
+
+#[test]
+fn bigint_i128_roundtrip() -> Result<(), HostError> {
+    let host = Host::default();
+    for i in [i128::MIN, i128::MIN + 1, -1, 0, 1, i128::MAX - 1, i128::MAX] {
+        let bin = host.test_bin_obj(&i.to_be_bytes())?;
+        let obj = host.bigint_from_i128(bin.to_object())?;
+        let back = host.bigint_to_i128(obj)?;
+        assert_eq!(host.obj_cmp(back.into(), bin.into())?, 0);
+    }
+
+    // i128::MAX + 1 does not fit.
+    let max = host.bigint_from_i128(host.test_bin_obj(&i128::MAX.to_be_bytes())?.to_object())?;
+    let over = host.bigint_add(max, host.bigint_from_u64(1)?)?;
+    let res = host.bigint_to_i128(over);
+    let code = ScHostValErrorCode::UnexpectedValType;
+    assert!(HostError::result_matches_err_status(res, code));
+
+    // Inputs must be exactly 16 bytes wide.
+    let short = host.test_bin_obj(&[1; 8])?;
+    assert!(host.bigint_from_i128(short.to_object()).is_err());
+    Ok(())
+}
+
+#[test]
+fn i128_pieces_and_arithmetic() -> Result<(), HostError> {
+    let host = Host::default();
+    let i128_obj = |i: i128| host.obj_from_i128_pieces(i as u64, (i as u128 >> 64) as u64);
+    for i in [i128::MIN, -1, 0, 1, 1 << 64, i128::MAX] {
+        let obj = i128_obj(i)?;
+        let bin = host.test_bin_obj(&i.to_be_bytes())?;
+        assert_eq!(host.obj_cmp(obj.into(), bin.into())?, 0);
+        assert_eq!(host.obj_to_i128_lo64(obj)?, i as u64);
+        assert_eq!(host.obj_to_i128_hi64(obj)?, (i as u128 >> 64) as u64);
+    }
+
+    let sum = host.i128_add(i128_obj(-5)?, i128_obj(1 << 70)?)?;
+    assert_eq!(
+        host.obj_cmp(sum.into(), i128_obj((1 << 70) - 5)?.into())?,
+        0
+    );
+    let product = host.i128_mul(i128_obj(-3)?, i128_obj(1 << 100)?)?;
+    assert_eq!(
+        host.obj_cmp(product.into(), i128_obj(-3 << 100)?.into())?,
+        0
+    );
+
+    let code = ScVmErrorCode::TrapIntegerOverflow;
+    let res = host.i128_add(i128_obj(i128::MAX)?, i128_obj(1)?);
+    assert!(HostError::result_matches_err_status(res, code));
+    let res = host.i128_mul(i128_obj(i128::MIN)?, i128_obj(-1)?);
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
+
+fn bigint_vec(host: &Host, xs: &[i64]) -> Result<Object, HostError> {
+    let mut v = host.vec_new(RawVal::from_void())?;
+    for x in xs {
+        v = host.vec_push(v, host.bigint_from_i64(*x)?.into())?;
+    }
+    Ok(v)
+}
+
+#[test]
+fn bigint_vec_sum_mod() -> Result<(), HostError> {
+    let host = Host::default();
+    let ten = host.bigint_from_u64(10)?;
+    let cases: [(&[i64], i64); 3] = [(&[5, 6, 7], 8), (&[], 0), (&[-3, 1], 8)];
+    for (xs, expected) in cases {
+        let res = host.bigint_vec_sum_mod(bigint_vec(&host, xs)?, ten)?;
+        assert_eq!(host.bigint_to_i64(res)?, expected);
+    }
+
+    let v = bigint_vec(&host, &[1])?;
+    let code = ScHostFnErrorCode::InputArgsInvalid;
+    let res = host.bigint_vec_sum_mod(v, host.bigint_from_u64(0)?);
+    assert!(HostError::result_matches_err_status(res, code));
+    let res = host.bigint_vec_sum_mod(v, host.bigint_from_i64(-10)?);
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
+
+#[test]
+fn bigint_vec_fold() -> Result<(), HostError> {
+    let host = Host::default();
+    let v = bigint_vec(&host, &[3, -2, 5])?;
+    let init = host.bigint_from_i64(4)?;
+    let cases = [
+        (BIGINT_FOLD_ADD, 10),
+        (BIGINT_FOLD_MUL, -120),
+        (BIGINT_FOLD_MIN, -2),
+        (BIGINT_FOLD_MAX, 5),
+    ];
+    for (op, expected) in cases {
+        let res = host.bigint_vec_fold(v, init, op.into())?;
+        assert_eq!(host.bigint_to_i64(res)?, expected);
+    }
+    // An empty vec folds to `init`.
+    let empty = bigint_vec(&host, &[])?;
+    let res = host.bigint_vec_fold(empty, init, BIGINT_FOLD_MUL.into())?;
+    assert_eq!(host.bigint_to_i64(res)?, 4);
+
+    let res = host.bigint_vec_fold(v, init, 6_u32.into());
+    let code = ScHostFnErrorCode::InputArgsInvalid;
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
@@ -1,7 +1,11 @@
 use crate::xdr::ScHostFnErrorCode;
 use crate::{
-    xdr::{ScHostObjErrorCode, ScObject, ScStatic, ScStatus, ScVal},
-    CheckedEnv, Host, HostError, RawVal, RawValConvertible,
+    host::xdr_size::{scval_xdr_len, MAX_SCVAL_XDR_DEPTH},
+    xdr::{
+        ScBigInt, ScHostObjErrorCode, ScMap, ScMapEntry, ScObject, ScStatic, ScStatus, ScVal,
+        WriteXdr,
+    },
+    CheckedEnv, Host, HostError, RawVal, RawValConvertible, Symbol,
 };
 use soroban_env_common::EnvBase;
 
@@ -13,7 +17,7 @@ use crate::{
         ContractDataEntry, Hash, LedgerEntry, LedgerEntryData, LedgerEntryExt, LedgerKey,
         LedgerKeyContractData,
     },
-    Object, Symbol,
+    Object,
 };
 #[cfg(feature = "vm")]
 use soroban_test_wasms::LINEAR_MEMORY;
@@ -164,6 +168,70 @@ fn binary_xdr_roundtrip() -> Result<(), HostError> {
     Ok(())
 }
 
+// Encodes `depth` ScVals, each but the innermost a vec holding the next.
+fn nested_vec_xdr(depth: u32) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for _ in 1..depth {
+        // SCV_OBJECT, present, SCO_VEC, one element.
+        buf.extend_from_slice(&[0, 0, 0, 4, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1]);
+    }
+    // SCV_U32 0.
+    buf.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 0]);
+    buf
+}
+
+#[test]
+fn deserialize_rejects_deep_nesting() -> Result<(), HostError> {
+    let host = Host::default();
+    let ok = host.test_bin_obj(&nested_vec_xdr(MAX_SCVAL_XDR_DEPTH))?;
+    host.deserialize_from_binary(ok.to_object())?;
+
+    let code = ScHostFnErrorCode::InputArgsInvalid;
+    let too_deep = host.test_bin_obj(&nested_vec_xdr(MAX_SCVAL_XDR_DEPTH + 1))?;
+    let res = host.deserialize_from_binary(too_deep.to_object());
+    assert!(HostError::result_matches_err_status(res, code));
+    // Deep enough to overflow the stack if parsed recursively.
+    let bomb = host.test_bin_obj(&nested_vec_xdr(10_000))?;
+    let res = host.deserialize_from_binary(bomb.to_object());
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
+
+#[test]
+fn scval_xdr_len_matches_encoding() -> Result<(), HostError> {
+    let host = Host::default();
+    let map = host.map_err(ScMap::try_from(vec![ScMapEntry {
+        key: ScVal::U32(1),
+        val: ScVal::Symbol(host.map_err("abcde".to_string().try_into())?),
+    }]))?;
+    let vals = [
+        ScVal::U63(5),
+        ScVal::I32(-3),
+        ScVal::Static(ScStatic::Void),
+        ScVal::Symbol(host.map_err("stellar".to_string().try_into())?),
+        ScVal::Bitset(0xff),
+        ScVal::Status(ScStatus::Ok),
+        ScVal::Status(ScStatus::HostObjectError(ScHostObjErrorCode::UnknownError)),
+        ScVal::Object(None),
+        ScVal::Object(Some(ScObject::Vec(host.test_scvec::<u32>(&[1, 2, 3])?))),
+        ScVal::Object(Some(ScObject::Map(map))),
+        ScVal::Object(Some(ScObject::U64(7))),
+        ScVal::Object(Some(ScObject::Bytes(
+            host.map_err(vec![1, 2, 3, 4, 5].try_into())?,
+        ))),
+        ScVal::Object(Some(ScObject::BigInt(ScBigInt::Zero))),
+        ScVal::Object(Some(ScObject::BigInt(ScBigInt::Negative(
+            host.map_err(vec![1, 2].try_into())?,
+        )))),
+    ];
+    for v in vals.iter() {
+        let mut buf = Vec::new();
+        host.map_err(v.write_xdr(&mut buf))?;
+        assert_eq!(scval_xdr_len(v), buf.len() as u64);
+    }
+    Ok(())
+}
+
 #[cfg(feature = "vm")]
 #[test]
 fn invoke_memcpy() -> Result<(), HostError> {
@@ -232,3 +300,207 @@ fn invoke_memcpy() -> Result<(), HostError> {
 
     Ok(())
 }
+
+#[test]
+fn binary_twos_complement_neg() -> Result<(), HostError> {
+    let host = Host::default();
+    let cases: [(&[u8], &[u8]); 4] = [
+        (&[0x00, 0x01], &[0xff, 0xff]),
+        (&[0xff, 0xff], &[0x00, 0x01]),
+        // Zero negates to itself.
+        (&[0x00, 0x00], &[0x00, 0x00]),
+        // The minimum value has no positive counterpart at the same width, so
+        // it overflows back to itself.
+        (&[0x80, 0x00], &[0x80, 0x00]),
+    ];
+    for (input, expected) in cases {
+        let obj = host.test_bin_obj(input)?;
+        let res = host.binary_twos_complement_neg(obj.to_object())?;
+        let obj_ref = host.test_bin_obj(expected)?;
+        assert_eq!(host.obj_cmp(res.into(), obj_ref.into())?, 0);
+    }
+    Ok(())
+}
+
+#[test]
+fn binary_add_be() -> Result<(), HostError> {
+    let host = Host::default();
+    let cases: [(&[u8], &[u8], &[u8], bool); 2] = [
+        (&[0x01, 0xff], &[0x00, 0x01], &[0x02, 0x00], false),
+        (&[0xff, 0xff], &[0x00, 0x02], &[0x00, 0x01], true),
+    ];
+    for (a, b, sum, carry) in cases {
+        let a = host.test_bin_obj(a)?;
+        let b = host.test_bin_obj(b)?;
+        let res = host.binary_add_be(a.to_object(), b.to_object())?;
+        let res_sum = host.vec_get(res, 0_u32.into())?;
+        let obj_ref = host.test_bin_obj(sum)?;
+        assert_eq!(host.obj_cmp(res_sum, obj_ref.into())?, 0);
+        let res_carry = host.vec_get(res, 1_u32.into())?;
+        assert_eq!(bool::try_from(res_carry)?, carry);
+    }
+
+    let a = host.test_bin_obj(&[1, 2, 3])?;
+    let b = host.test_bin_obj(&[1, 2])?;
+    let res = host.binary_add_be(a.to_object(), b.to_object());
+    let code = ScHostFnErrorCode::InputArgsInvalid;
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
+
+#[test]
+fn binary_rle_roundtrip() -> Result<(), HostError> {
+    let host = Host::default();
+    let mut repetitive = vec![0_u8; 600];
+    repetitive.extend_from_slice(&[7; 3]);
+    let no_runs: Vec<u8> = (0..=255).collect();
+    for (input, encoded_len) in [(repetitive, 8_u32), (no_runs, 512), (vec![], 0)] {
+        let obj = host.test_bin_obj(&input)?;
+        let encoded = host.binary_rle_encode(obj.to_object())?;
+        // A run of 600 splits into runs of 255, 255 and 90.
+        assert_eq!(u32::try_from(host.binary_len(encoded)?)?, encoded_len);
+        let decoded = host.binary_rle_decode(encoded)?;
+        assert_eq!(host.obj_cmp(decoded.into(), obj.into())?, 0);
+    }
+
+    let code = ScHostFnErrorCode::InputArgsInvalid;
+    for malformed in [&[1_u8, 2, 3][..], &[1, 0]] {
+        let obj = host.test_bin_obj(malformed)?;
+        let res = host.binary_rle_decode(obj.to_object());
+        assert!(HostError::result_matches_err_status(res, code));
+    }
+    Ok(())
+}
+
+#[test]
+fn scval_serialization_skips_host_objects() -> Result<(), HostError> {
+    let host = Host::default();
+    let v = ScVal::Object(Some(ScObject::Vec(host.test_scvec::<u32>(&[1, 2])?)));
+    let bytes = host.serialize_scval(&v)?;
+    assert_eq!(host.deserialize_scval(&bytes)?, v);
+
+    // Matches the encoding produced through the host.
+    let bo = host.serialize_to_binary(host.inject_val(&v)?)?;
+    let bo_ref = host.test_bin_obj(&bytes)?;
+    assert_eq!(host.obj_cmp(bo.into(), bo_ref.into())?, 0);
+
+    let code = ScHostFnErrorCode::InputArgsInvalid;
+    let res = host.deserialize_scval(&nested_vec_xdr(MAX_SCVAL_XDR_DEPTH + 1));
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
+
+#[test]
+fn binary_u32_be_accessors() -> Result<(), HostError> {
+    let host = Host::default();
+    let obj = host.test_bin_obj(&[0xff, 0x12, 0x34, 0x56, 0x78, 0xff])?;
+    let word = host.binary_get_u32_be(obj.to_object(), 1_u32.into())?;
+    assert_eq!(u32::try_from(word)?, 0x12345678);
+
+    let res = host.binary_set_u32_be(obj.to_object(), 2_u32.into(), 0xaabbccdd_u32.into())?;
+    let obj_ref = host.test_bin_obj(&[0xff, 0x12, 0xaa, 0xbb, 0xcc, 0xdd])?;
+    assert_eq!(host.obj_cmp(res.into(), obj_ref.into())?, 0);
+
+    let code = ScHostObjErrorCode::VecIndexOutOfBound;
+    let res = host.binary_get_u32_be(obj.to_object(), 3_u32.into());
+    assert!(HostError::result_matches_err_status(res, code));
+    let res = host.binary_set_u32_be(obj.to_object(), 3_u32.into(), 0_u32.into());
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
+
+#[test]
+fn binary_ct_eq() -> Result<(), HostError> {
+    let host = Host::default();
+    let a = host.test_bin_obj(&[1, 2, 3, 4])?.to_object();
+    let same = host.test_bin_obj(&[1, 2, 3, 4])?.to_object();
+    let first = host.test_bin_obj(&[0, 2, 3, 4])?.to_object();
+    let last = host.test_bin_obj(&[1, 2, 3, 5])?.to_object();
+    let short = host.test_bin_obj(&[1, 2, 3])?.to_object();
+    assert!(bool::try_from(host.binary_ct_eq(a, same)?)?);
+    assert!(!bool::try_from(host.binary_ct_eq(a, first)?)?);
+    assert!(!bool::try_from(host.binary_ct_eq(a, last)?)?);
+    assert!(!bool::try_from(host.binary_ct_eq(a, short)?)?);
+    Ok(())
+}
+
+#[test]
+fn binary_vec_roundtrip() -> Result<(), HostError> {
+    let host = Host::default();
+    let bin = host.test_bin_obj(&[0, 7, 255])?;
+    let vec = host.vec_from_binary(bin.to_object())?;
+    let want = host.test_vec_obj::<u32>(&[0, 7, 255])?;
+    assert_eq!(host.obj_cmp(vec.into(), want.into())?, 0);
+    let back = host.binary_from_vec(vec)?;
+    assert_eq!(host.obj_cmp(back.into(), bin.into())?, 0);
+
+    let res = host.binary_from_vec(host.test_vec_obj::<u32>(&[1, 256])?.to_object());
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostFnErrorCode::InputArgsInvalid
+    ));
+    let res = host.binary_from_vec(host.test_vec_obj::<i32>(&[1])?.to_object());
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostFnErrorCode::InputArgsWrongType
+    ));
+    Ok(())
+}
+
+#[test]
+fn obj_serialized_size_matches_serialization() -> Result<(), HostError> {
+    let host = Host::default();
+    let inner = host.test_vec_obj::<u32>(&[1, 2, 3])?.to_object();
+    let mut map = host.map_new()?;
+    map = host.map_put(map, Symbol::from_str("a").into(), inner.into())?;
+    map = host.map_put(map, 7_u32.into(), host.bigint_from_i64(-300)?.into())?;
+    let vals: [RawVal; 7] = [
+        7_u32.into(),
+        Symbol::from_str("abcde").into(),
+        host.test_bin_obj(&[1, 2, 3, 4, 5])?.into(),
+        host.bigint_from_u64(0)?.into(),
+        host.obj_from_u64(u64::MAX)?.into(),
+        inner.into(),
+        map.into(),
+    ];
+    for v in vals {
+        let size = u32::try_from(host.obj_serialized_size(v)?)?;
+        let bin = host.serialize_to_binary(v)?;
+        assert_eq!(size, u32::try_from(host.binary_len(bin)?)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn try_binary_copy_to_slice_reports_out_of_bound() -> Result<(), HostError> {
+    let host = Host::default();
+    let obj = host.test_bin_obj(&[1, 2, 3, 4])?.to_object();
+    let mut buf = [0u8; 2];
+    host.try_binary_copy_to_slice(obj, 1_u32.into(), &mut buf)?;
+    assert_eq!(buf, [2, 3]);
+    let res = host.try_binary_copy_to_slice(obj, 3_u32.into(), &mut buf);
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostObjErrorCode::VecIndexOutOfBound
+    ));
+    Ok(())
+}
+
+#[test]
+fn try_binary_copy_from_slice_grows_only_when_asked() -> Result<(), HostError> {
+    let host = Host::default();
+    let obj = host.test_bin_obj(&[1, 2, 3])?.to_object();
+    let res = host.try_binary_copy_from_slice(obj, 1_u32.into(), &[7, 8], false)?;
+    let want = host.test_bin_obj(&[1, 7, 8])?;
+    assert_eq!(host.obj_cmp(res.into(), want.into())?, 0);
+
+    let res = host.try_binary_copy_from_slice(obj, 2_u32.into(), &[7, 8], false);
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostObjErrorCode::VecIndexOutOfBound
+    ));
+    let res = host.try_binary_copy_from_slice(obj, 2_u32.into(), &[7, 8], true)?;
+    let want = host.test_bin_obj(&[1, 2, 7, 8])?;
+    assert_eq!(host.obj_cmp(res.into(), want.into())?, 0);
+    Ok(())
+}
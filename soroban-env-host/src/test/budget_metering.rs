@@ -1,6 +1,8 @@
 use crate::{
-    budget::{Budget, CostType},
-    xdr::{ScMap, ScMapEntry, ScObject, ScVal},
+    budget::{Budget, CostType, TYPE_LIMIT_EXCEEDED},
+    events::{DebugArg, HostEvent},
+    storage::Storage,
+    xdr::{ScMap, ScMapEntry, ScObject, ScVal, ScVmErrorCode},
     CheckedEnv, Host, HostError, Symbol,
 };
 use soroban_test_wasms::VEC;
@@ -60,3 +62,230 @@ fn vm_hostfn_invocation() -> Result<(), HostError> {
 
     Ok(())
 }
+
+#[test]
+fn budget_snapshot_and_restore() -> Result<(), HostError> {
+    let host = Host::test_host()
+        .test_budget()
+        .enable_model(CostType::BigIntMul);
+    let x = host.bigint_from_u64(u64::MAX)?;
+    let y = host.bigint_from_u64(u64::MAX)?;
+
+    let snap = host.get_budget(|budget| budget.snapshot());
+    let (cpu, mem, input) = host.get_budget(|budget| {
+        (
+            budget.get_cpu_insns_count(),
+            budget.get_mem_bytes_count(),
+            budget.get_input(CostType::BigIntMul),
+        )
+    });
+
+    host.bigint_mul(x, y)?;
+    host.get_budget(|budget| {
+        assert!(budget.get_input(CostType::BigIntMul) > input);
+        assert!(budget.get_cpu_insns_count() > cpu);
+    });
+
+    host.get_budget(|budget| budget.restore(snap));
+    host.get_budget(|budget| {
+        assert_eq!(budget.get_input(CostType::BigIntMul), input);
+        assert_eq!(budget.get_cpu_insns_count(), cpu);
+        assert_eq!(budget.get_mem_bytes_count(), mem);
+    });
+    Ok(())
+}
+
+#[test]
+fn budget_tracker_breakdown() -> Result<(), HostError> {
+    let host = Host::test_host().test_budget();
+    let x = host.bigint_from_u64(1000)?;
+    let y = host.bigint_from_u64(7)?;
+    host.bigint_mul(x, y)?;
+    host.bigint_mul(x, y)?;
+
+    let tracker = host.get_budget(|budget| budget.get_tracker());
+    assert_eq!(tracker.len(), CostType::variants().len());
+    // Two multiplications, each charged on the larger bit-length (10 bits).
+    assert_eq!(tracker[&CostType::BigIntMul], (2, 20));
+    assert_eq!(tracker[&CostType::BigIntNew].0, 2);
+    assert_eq!(tracker[&CostType::BigIntPow], (0, 0));
+    Ok(())
+}
+
+#[test]
+fn per_type_limit_exceeded() -> Result<(), HostError> {
+    let budget = Budget::default().with_type_limit(CostType::BigIntMul, 15);
+    let host = Host::with_storage_and_budget(Default::default(), budget)
+        .test_budget()
+        .enable_model(CostType::BigIntMul);
+    let x = host.bigint_from_u64(1000)?;
+    let y = host.bigint_from_u64(7)?;
+
+    // Charged 10 (the larger bit-length), under the cap of 15.
+    host.bigint_mul(x, y)?;
+    // Another 10 exceeds the cap even though the global limits have room.
+    let err = host.bigint_mul(x, y).unwrap_err();
+    assert_eq!(err.status, TYPE_LIMIT_EXCEEDED);
+    assert!(!err.is_recoverable());
+    // The error names the cost type whose limit was hit.
+    let events = err.events.expect("type limit error carries a debug event");
+    match events.0.as_slice() {
+        [HostEvent::Debug(de)] => match de.args.as_slice() {
+            [DebugArg::CostType(ty)] => assert_eq!(*ty, CostType::BigIntMul),
+            args => panic!("unexpected debug args: {:?}", args),
+        },
+        events => panic!("unexpected events: {:?}", events),
+    }
+    host.get_budget(|budget| {
+        assert!(budget.get_cpu_insns_count() < budget.0.borrow().cpu_insns.get_limit());
+    });
+    Ok(())
+}
+
+#[test]
+fn dry_run_budget_never_fails() -> Result<(), HostError> {
+    let budget = Budget::new_dry_run().with_type_limit(CostType::BigIntMul, 5);
+    let host = Host::with_storage_and_budget(Default::default(), budget)
+        .test_budget()
+        .enable_model(CostType::BigIntMul);
+    host.get_budget(|budget| budget.reset_limits(1, 1));
+    let x = host.bigint_from_u64(1000)?;
+    let y = host.bigint_from_u64(7)?;
+
+    // Both the per-type and the global limits are exceeded, yet nothing fails.
+    host.bigint_mul(x, y)?;
+    host.bigint_mul(x, y)?;
+    host.get_budget(|budget| {
+        assert!(budget.is_dry_run());
+        assert_eq!(budget.get_tracker()[&CostType::BigIntMul], (2, 20));
+        assert_eq!(budget.get_cpu_insns_count(), 200);
+        assert_eq!(budget.get_mem_bytes_count(), 20);
+    });
+    Ok(())
+}
+
+#[test]
+fn budget_exemption_suspends_charging() -> Result<(), HostError> {
+    let host = Host::test_host()
+        .test_budget()
+        .enable_model(CostType::BigIntMul);
+    let x = host.bigint_from_u64(1000)?;
+    let y = host.bigint_from_u64(7)?;
+    let cpu = host.get_budget(|budget| budget.get_cpu_insns_count());
+
+    host.with_budget_exemption(|| {
+        host.bigint_mul(x, y)?;
+        host.bigint_mul(x, y)
+    })?;
+    host.get_budget(|budget| {
+        assert_eq!(budget.get_cpu_insns_count(), cpu);
+        assert_eq!(budget.get_tracker()[&CostType::BigIntMul], (0, 0));
+    });
+
+    // Charging resumes afterwards.
+    host.bigint_mul(x, y)?;
+    host.get_budget(|budget| {
+        assert!(budget.get_cpu_insns_count() > cpu);
+        assert_eq!(budget.get_tracker()[&CostType::BigIntMul], (1, 10));
+    });
+    Ok(())
+}
+
+#[test]
+fn budget_exemption_ends_when_closure_fails() -> Result<(), HostError> {
+    let host = Host::test_host()
+        .test_budget()
+        .enable_model(CostType::BigIntMul);
+    let x = host.bigint_from_u64(1000)?;
+    let y = host.bigint_from_u64(7)?;
+
+    let res: Result<(), HostError> = host.with_budget_exemption(|| {
+        host.bigint_mul(x, y)?;
+        Err(host.err_general("exempt work failed"))
+    });
+    assert!(res.is_err());
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        host.with_budget_exemption(|| -> Result<(), HostError> { panic!("exempt work panicked") })
+    }));
+    assert!(res.is_err());
+
+    // Charging resumes either way.
+    host.bigint_mul(x, y)?;
+    host.get_budget(|budget| {
+        assert_eq!(budget.get_tracker()[&CostType::BigIntMul], (1, 10));
+    });
+    Ok(())
+}
+
+#[test]
+fn with_budget_limit_caps_cpu() -> Result<(), HostError> {
+    let host = Host::with_budget_limit(Storage::default(), 150);
+    host.get_budget(|budget| {
+        assert_eq!(budget.0.borrow().cpu_insns.get_limit(), 150);
+        budget.reset_models();
+    });
+    let host = host.enable_model(CostType::BigIntMul);
+    let x = host.bigint_from_u64(1000)?;
+    let y = host.bigint_from_u64(7)?;
+    host.bigint_mul(x, y)?;
+    let res = host.bigint_mul(x, y);
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScVmErrorCode::TrapMemLimitExceeded
+    ));
+    Ok(())
+}
+
+#[test]
+fn deep_clone_metered_charges_per_object_and_element() -> Result<(), HostError> {
+    let host = Host::test_host().test_budget();
+    let v = host.test_vec_obj::<u32>(&[1, 2, 3])?;
+    host.test_bin_obj(&[0; 5])?;
+    host.get_budget(|budget| budget.reset_inputs());
+
+    let clone = host.try_deep_clone_metered()?;
+    host.get_budget(|budget| {
+        assert_eq!(budget.get_input(CostType::HostObjAllocSlot), 2);
+        assert_eq!(budget.get_input(CostType::ImVecMutEntry), 3);
+        assert_eq!(budget.get_input(CostType::BytesClone), 5);
+    });
+    assert_eq!(u32::try_from(clone.vec_len(v.to_object())?)?, 3);
+
+    // A budget too small for the copy fails it.
+    host.get_budget(|budget| budget.reset_limits(0, 0));
+    let host = host.enable_model(CostType::HostObjAllocSlot);
+    assert!(host.try_deep_clone_metered().is_err());
+    Ok(())
+}
+
+#[test]
+fn obj_eq_metering() -> Result<(), HostError> {
+    let host = Host::test_host().test_budget();
+    let v1 = host.test_vec_obj::<u32>(&[1, 2, 3])?;
+    let v2 = host.test_vec_obj::<u32>(&[1, 2])?;
+    let v3 = host.test_vec_obj::<u32>(&[1, 2, 4])?;
+    // Different lengths are unequal without charging for elements.
+    host.obj_eq(v1.clone().into(), v2.into())?;
+    host.get_budget(|budget| assert_eq!(budget.get_input(CostType::HostVecCmp), 0));
+    host.obj_eq(v1.into(), v3.into())?;
+    host.get_budget(|budget| assert_eq!(budget.get_input(CostType::HostVecCmp), 3));
+    Ok(())
+}
+
+#[test]
+fn obj_cmp_metering() -> Result<(), HostError> {
+    let host = Host::test_host().test_budget();
+    let v1 = host.test_vec_obj::<u32>(&[1, 2, 3])?;
+    let v2 = host.test_vec_obj::<u32>(&[1, 2])?;
+    host.obj_cmp(v1.into(), v2.into())?;
+    let b1 = host.bigint_from_u64(1000)?;
+    let b2 = host.bigint_from_u64(7)?;
+    host.obj_cmp(b1.into(), b2.into())?;
+    host.bigint_cmp(b1, b2)?;
+    host.get_budget(|budget| {
+        assert_eq!(budget.get_input(CostType::HostVecCmp), 2);
+        assert_eq!(budget.get_input(CostType::HostMapCmp), 0);
+        assert_eq!(budget.get_input(CostType::BigIntCmp), 20);
+    });
+    Ok(())
+}
@@ -0,0 +1,483 @@
+use crate::{
+    host::{Frame, DEFAULT_MAX_FRAME_DEPTH, EVENT_LIMIT_EXCEEDED, MAX_FRAME_DEPTH_EXCEEDED},
+    xdr::{Hash, HostFunction, ScHostContextErrorCode, ScHostObjErrorCode, ScObject, ScVal},
+    CheckedEnv, FrameInfo, Host, HostError, RawVal, Status,
+};
+#[cfg(feature = "testutils")]
+use crate::{
+    host::{CONTRACT_ALREADY_EXISTS, CONTRACT_REENTRY, NATIVE_CONTRACT_PANIC},
+    xdr::Uint256,
+    ContractFunctionSet, Object, Symbol,
+};
+#[cfg(feature = "testutils")]
+use sha2::{Digest, Sha256};
+#[cfg(feature = "testutils")]
+use std::rc::Rc;
+
+fn call_stack_ids(host: &Host) -> Result<Vec<Vec<u8>>, HostError> {
+    let stack = host.get_call_stack()?;
+    let len = u32::try_from(host.vec_len(stack)?)?;
+    let mut ids = Vec::new();
+    for i in 0..len {
+        match host.from_host_val(host.vec_get(stack, i.into())?)? {
+            ScVal::Object(Some(ScObject::Bytes(b))) => ids.push(b.to_vec()),
+            _ => panic!("Wrong type"),
+        }
+    }
+    Ok(ids)
+}
+
+#[test]
+fn call_stack_lists_contracts_oldest_first() -> Result<(), HostError> {
+    let host = Host::default();
+    let ids = host.with_frame(Frame::HostFunction(HostFunction::Call), || {
+        host.with_frame(Frame::Token(Hash([1; 32])), || {
+            host.with_frame(Frame::Token(Hash([2; 32])), || call_stack_ids(&host))
+        })
+    })?;
+    assert_eq!(ids, vec![vec![1; 32], vec![2; 32]]);
+    Ok(())
+}
+
+#[test]
+fn current_frame_info_describes_top_frame() -> Result<(), HostError> {
+    let host = Host::default();
+    assert_eq!(host.current_frame_info(), None);
+    assert_eq!(host.context_depth(), 0);
+    host.with_frame(Frame::HostFunction(HostFunction::Call), || {
+        assert_eq!(
+            host.current_frame_info(),
+            Some(FrameInfo::HostFunction(HostFunction::Call))
+        );
+        host.with_frame(Frame::Token(Hash([1; 32])), || {
+            assert_eq!(
+                host.current_frame_info(),
+                Some(FrameInfo::Token(Hash([1; 32])))
+            );
+            assert_eq!(host.context_depth(), 2);
+            Ok(())
+        })
+    })?;
+    assert_eq!(host.context_depth(), 0);
+    Ok(())
+}
+
+#[test]
+fn call_stack_empty_outside_contracts() -> Result<(), HostError> {
+    let host = Host::default();
+    let len: RawVal = host.vec_len(host.get_call_stack()?)?;
+    assert_eq!(len.get_payload(), RawVal::from_u32(0).get_payload());
+    Ok(())
+}
+
+#[test]
+fn reset_discards_objects_and_events() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let vec = host.vec_new(RawVal::from_void())?;
+    host.log_value(vec.into())?;
+    assert_eq!(host.get_events()?.0.len(), 1);
+
+    let err = host
+        .with_frame(Frame::Token(Hash([0; 32])), || host.reset())
+        .unwrap_err();
+    let status: Status = ScHostContextErrorCode::UnknownError.into();
+    assert_eq!(err.status, status);
+    assert!(HostError::result_matches_debug_msg::<()>(
+        Err(err),
+        "cannot reset host during an invocation"
+    ));
+
+    host.reset()?;
+    assert!(host.vec_len(vec).is_err());
+    assert!(host.get_events()?.0.is_empty());
+    // Ledger info survives.
+    host.get_ledger_sequence()?;
+    Ok(())
+}
+
+#[test]
+fn objects_persist_unless_frame_rolls_back() -> Result<(), HostError> {
+    let host = Host::default();
+    assert_eq!(host.objects_len(), 0);
+    host.with_frame(Frame::HostFunction(HostFunction::Call), || {
+        host.with_frame(Frame::Token(Hash([1; 32])), || {
+            host.obj_from_u64(1)?;
+            host.obj_from_u64(2)
+        })?;
+        // Objects from a frame that succeeded outlive it, even if unused.
+        assert_eq!(host.objects_len(), 2);
+        let res: Result<(), HostError> = host.with_frame(Frame::Token(Hash([1; 32])), || {
+            host.obj_from_u64(3)?;
+            Err(host.err_general("fail"))
+        });
+        assert!(res.is_err());
+        assert_eq!(host.objects_len(), 2);
+        Ok(())
+    })?;
+    assert_eq!(host.objects_len(), 2);
+    Ok(())
+}
+
+fn nest_frames(host: &Host, remaining: u32) -> Result<(), HostError> {
+    if remaining == 0 {
+        return Ok(());
+    }
+    host.with_frame(Frame::Token(Hash([0; 32])), || {
+        nest_frames(host, remaining - 1)
+    })
+}
+
+#[test]
+fn frame_depth_is_bounded() -> Result<(), HostError> {
+    let host = Host::default();
+    assert_eq!(host.get_max_frame_depth(), DEFAULT_MAX_FRAME_DEPTH);
+    host.set_max_frame_depth(10);
+    nest_frames(&host, 10)?;
+    let err = nest_frames(&host, 11).unwrap_err();
+    assert_eq!(err.status, MAX_FRAME_DEPTH_EXCEEDED);
+    // Like a wasm stack overflow, it is not recoverable.
+    assert!(!err.is_recoverable());
+    Ok(())
+}
+
+#[cfg(feature = "testutils")]
+struct RecursiveContract;
+
+#[cfg(feature = "testutils")]
+impl ContractFunctionSet for RecursiveContract {
+    fn call(&self, func: &Symbol, host: &Host, args: &[RawVal]) -> Option<RawVal> {
+        // Call ourselves until the host refuses, then surface its status.
+        let id = host.get_current_contract().ok()?;
+        let args = host.vec_new(().into()).ok()?;
+        match CheckedEnv::call(host, id, *func, args) {
+            Ok(v) => Some(v),
+            Err(e) => Some(e.status.to_raw()),
+        }
+    }
+}
+
+#[cfg(feature = "testutils")]
+#[test]
+fn recursive_contract_hits_frame_depth_limit() -> Result<(), HostError> {
+    let host = Host::default();
+    let id = host.test_bin_obj(&[0; 32])?.to_object();
+    host.register_test_contract(id, Rc::new(RecursiveContract))?;
+    let args = host.vec_new(().into())?;
+    let res = CheckedEnv::call(&host, id, Symbol::from_str("recurse"), args)?;
+    assert_eq!(
+        res.get_payload(),
+        MAX_FRAME_DEPTH_EXCEEDED.to_raw().get_payload()
+    );
+    Ok(())
+}
+
+// Calls itself with its u32 argument decremented until it reaches 1.
+#[cfg(feature = "testutils")]
+struct CountdownContract;
+
+#[cfg(feature = "testutils")]
+impl ContractFunctionSet for CountdownContract {
+    fn call(&self, func: &Symbol, host: &Host, args: &[RawVal]) -> Option<RawVal> {
+        let n = u32::try_from(*args.first()?).ok()?;
+        if n <= 1 {
+            return Some(().into());
+        }
+        let id = host.get_current_contract().ok()?;
+        let args = host
+            .vec_push(host.vec_new(().into()).ok()?, (n - 1).into())
+            .ok()?;
+        CheckedEnv::call(host, id, *func, args).ok()
+    }
+}
+
+#[cfg(feature = "testutils")]
+#[test]
+fn peak_call_depth_tracks_deepest_stack() -> Result<(), HostError> {
+    let host = Host::default();
+    let id = host.test_bin_obj(&[0; 32])?.to_object();
+    host.register_test_contract(id, Rc::new(CountdownContract))?;
+    let call = |n: u32| -> Result<u32, HostError> {
+        let args = host.vec_push(host.vec_new(().into())?, n.into())?;
+        CheckedEnv::call(&host, id, Symbol::from_str("count"), args)?;
+        host.peak_call_depth()
+    };
+    assert_eq!(call(5)?, 5);
+    // Each top-level invocation starts afresh.
+    assert_eq!(call(2)?, 2);
+    Ok(())
+}
+
+// Returns the arguments it was called with, as captured by the host.
+#[cfg(feature = "testutils")]
+struct EchoArgsContract;
+
+#[cfg(feature = "testutils")]
+impl ContractFunctionSet for EchoArgsContract {
+    fn call(&self, _func: &Symbol, host: &Host, _args: &[RawVal]) -> Option<RawVal> {
+        host.get_current_call_args().ok().map(|v| v.to_raw())
+    }
+}
+
+#[cfg(feature = "testutils")]
+#[test]
+fn get_current_call_args_returns_captured_args() -> Result<(), HostError> {
+    let host = Host::default();
+    let id = host.test_bin_obj(&[0; 32])?.to_object();
+    host.register_test_contract(id, Rc::new(EchoArgsContract))?;
+    let args = host.test_vec_obj::<u32>(&[1, 2, 3])?.to_object();
+    let echoed = CheckedEnv::call(&host, id, Symbol::from_str("echo"), args)?;
+    assert_eq!(host.obj_cmp(echoed, args.to_raw())?, 0);
+
+    // Host function frames have no call arguments.
+    let res = host.with_frame(Frame::HostFunction(HostFunction::Call), || {
+        host.get_current_call_args()
+    });
+    let code = ScHostContextErrorCode::NoContractRunning;
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
+
+// Adds an object, then panics.
+#[cfg(feature = "testutils")]
+struct PanicContract;
+
+#[cfg(feature = "testutils")]
+impl ContractFunctionSet for PanicContract {
+    fn call(&self, _func: &Symbol, host: &Host, _args: &[RawVal]) -> Option<RawVal> {
+        host.obj_from_u64(1).unwrap();
+        panic!("contract bug")
+    }
+}
+
+#[cfg(feature = "testutils")]
+#[test]
+fn test_contract_panic_becomes_error() -> Result<(), HostError> {
+    let host = Host::default();
+    let id = host.test_bin_obj(&[0; 32])?.to_object();
+    host.register_test_contract(id, Rc::new(PanicContract))?;
+    let args = host.test_vec_obj::<u32>(&[])?.to_object();
+    let objects = host.objects_len();
+    let res = CheckedEnv::call(&host, id, Symbol::from_str("go"), args);
+    assert!(HostError::result_matches_err_status(
+        res,
+        NATIVE_CONTRACT_PANIC
+    ));
+    // The host is still usable, and the contract's object was rolled back.
+    assert_eq!(host.objects_len(), objects);
+    host.obj_from_u64(2)?;
+    assert_eq!(host.context_depth(), 0);
+    Ok(())
+}
+
+// Returns the symbol naming the function it was called with.
+#[cfg(feature = "testutils")]
+struct EchoFuncContract;
+
+#[cfg(feature = "testutils")]
+impl ContractFunctionSet for EchoFuncContract {
+    fn call(&self, _func: &Symbol, host: &Host, _args: &[RawVal]) -> Option<RawVal> {
+        host.get_current_call_func().ok()
+    }
+}
+
+#[cfg(feature = "testutils")]
+#[test]
+fn get_current_call_func_returns_invoked_symbol() -> Result<(), HostError> {
+    let host = Host::default();
+    let id = host.test_bin_obj(&[0; 32])?.to_object();
+    host.register_test_contract(id, Rc::new(EchoFuncContract))?;
+    let args = host.test_vec_obj::<u32>(&[])?.to_object();
+    for name in ["transfer", "balance"] {
+        let sym = Symbol::from_str(name);
+        let echoed = CheckedEnv::call(&host, id, sym, args)?;
+        assert_eq!(echoed.get_payload(), sym.to_raw().get_payload());
+    }
+
+    let res = host.with_frame(Frame::HostFunction(HostFunction::Call), || {
+        host.get_current_call_func()
+    });
+    let code = ScHostContextErrorCode::NoContractRunning;
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
+
+#[cfg(feature = "testutils")]
+#[test]
+fn register_test_contract_twice_fails() -> Result<(), HostError> {
+    let host = Host::default();
+    let id = host.test_bin_obj(&[0; 32])?.to_object();
+    host.register_test_contract(id, Rc::new(CountdownContract))?;
+    let res = host.register_test_contract(id, Rc::new(CountdownContract));
+    assert!(HostError::result_matches_err_status(
+        res,
+        CONTRACT_ALREADY_EXISTS
+    ));
+    Ok(())
+}
+
+// Calls the contract named by its first argument, passing on the rest, and
+// returns void once the arguments run out.
+#[cfg(feature = "testutils")]
+struct RelayContract;
+
+#[cfg(feature = "testutils")]
+impl ContractFunctionSet for RelayContract {
+    fn call(&self, func: &Symbol, host: &Host, args: &[RawVal]) -> Option<RawVal> {
+        let (next, rest) = match args.split_first() {
+            None => return Some(().into()),
+            Some((next, rest)) => (next.try_into().ok()?, rest),
+        };
+        let mut rest_vec = host.vec_new(().into()).ok()?;
+        for arg in rest {
+            rest_vec = host.vec_push(rest_vec, *arg).ok()?;
+        }
+        match CheckedEnv::call(host, next, *func, rest_vec) {
+            Ok(v) => Some(v),
+            Err(e) => Some(e.status.to_raw()),
+        }
+    }
+}
+
+// Calls A, which calls B, which calls back into A.
+#[cfg(feature = "testutils")]
+fn call_a_b_a(host: &Host) -> Result<RawVal, HostError> {
+    let a = host.test_bin_obj(&[1; 32])?.to_object();
+    let b = host.test_bin_obj(&[2; 32])?.to_object();
+    host.register_test_contract(a, Rc::new(RelayContract))?;
+    host.register_test_contract(b, Rc::new(RelayContract))?;
+    let mut args = host.vec_new(().into())?;
+    args = host.vec_push(args, b.into())?;
+    args = host.vec_push(args, a.into())?;
+    CheckedEnv::call(host, a, Symbol::from_str("relay"), args)
+}
+
+#[cfg(feature = "testutils")]
+#[test]
+fn reentrancy_allowed_by_default() -> Result<(), HostError> {
+    let host = Host::default();
+    assert!(call_a_b_a(&host)?.is_void());
+    Ok(())
+}
+
+#[cfg(feature = "testutils")]
+#[test]
+fn reentrancy_guard_rejects_reentry() -> Result<(), HostError> {
+    let host = Host::default();
+    host.set_reentrancy_guard(true);
+    let res = call_a_b_a(&host)?;
+    assert_eq!(res.get_payload(), CONTRACT_REENTRY.to_raw().get_payload());
+    Ok(())
+}
+
+fn emit_events(host: &Host, n: u32) -> Result<(), HostError> {
+    host.with_frame(Frame::Token(Hash([1; 32])), || {
+        for i in 0..n {
+            host.contract_event(host.test_vec_obj::<u32>(&[i])?.to_object(), i.into())?;
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn events_per_invocation_are_capped() -> Result<(), HostError> {
+    let host = Host::default();
+    host.set_max_events_per_invocation(3);
+    emit_events(&host, 3)?;
+    let res = emit_events(&host, 4);
+    assert!(HostError::result_matches_err_status(
+        res,
+        EVENT_LIMIT_EXCEEDED
+    ));
+    // The count starts over with the next invocation.
+    emit_events(&host, 3)?;
+    // Debug events are not counted, so an error past the cap keeps its own
+    // status.
+    let res = host.with_frame(Frame::Token(Hash([1; 32])), || {
+        emit_events(&host, 3)?;
+        host.vec_get(host.test_vec_obj::<u32>(&[])?.to_object(), 0_u32.into())
+    });
+    let code = ScHostObjErrorCode::VecIndexOutOfBound;
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
+
+// Initializes successfully if its first argument is non-zero.
+#[cfg(feature = "testutils")]
+struct InitContract;
+
+#[cfg(feature = "testutils")]
+impl ContractFunctionSet for InitContract {
+    fn call(&self, _func: &Symbol, _host: &Host, args: &[RawVal]) -> Option<RawVal> {
+        match u32::try_from(*args.first()?).ok()? {
+            0 => None,
+            _ => Some(().into()),
+        }
+    }
+}
+
+#[cfg(feature = "testutils")]
+#[test]
+fn create_and_init_contract_rolls_back_failed_init() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let parent = Hash([1; 32]);
+    let deploy = |salt: u8, arg: u32| -> Result<(Hash, Result<Object, HostError>), HostError> {
+        let preimage = host.id_preimage_from_contract(parent.clone(), Uint256([salt; 32]))?;
+        let id = Hash(Sha256::digest(&preimage).into());
+        host.register_test_contract(host.test_bin_obj(&id.0)?.to_object(), Rc::new(InitContract))?;
+        let res = host.with_frame(Frame::TestContract(parent.clone()), || {
+            let wasm = host.test_bin_obj(&[0; 4])?.to_object();
+            let salt = host.test_bin_obj(&[salt; 32])?.to_object();
+            let args = host.vec_push(host.vec_new(().into())?, arg.into())?;
+            Ok(host.create_and_init_contract(wasm, salt, Symbol::from_str("init"), args))
+        })?;
+        Ok((id, res))
+    };
+    let has_code = |id: Hash| -> Result<bool, HostError> {
+        let key = host.contract_code_ledger_key(id);
+        host.visit_storage(|storage| storage.has(&key))
+    };
+
+    let (id, res) = deploy(1, 1)?;
+    let id_obj = res?;
+    assert_eq!(host.hash_from_obj_input("id", id_obj)?, id);
+    assert!(has_code(id)?);
+
+    let (id, res) = deploy(2, 0)?;
+    assert!(res.is_err());
+    assert!(!has_code(id)?);
+    Ok(())
+}
+
+// Initializes successfully only if invoked by the contract with the given ID.
+#[cfg(feature = "testutils")]
+struct InvokerCheckContract(Hash);
+
+#[cfg(feature = "testutils")]
+impl ContractFunctionSet for InvokerCheckContract {
+    fn call(&self, _func: &Symbol, host: &Host, _args: &[RawVal]) -> Option<RawVal> {
+        let invoker = host.get_invoking_contract().ok()?;
+        let invoker = host.hash_from_obj_input("invoker", invoker).ok()?;
+        (invoker == self.0).then(|| ().into())
+    }
+}
+
+#[cfg(feature = "testutils")]
+#[test]
+fn create_and_init_contract_init_is_invoked_by_deployer() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let parent = Hash([1; 32]);
+    let preimage = host.id_preimage_from_contract(parent.clone(), Uint256([1; 32]))?;
+    let id = Hash(Sha256::digest(&preimage).into());
+    host.register_test_contract(
+        host.test_bin_obj(&id.0)?.to_object(),
+        Rc::new(InvokerCheckContract(parent.clone())),
+    )?;
+    let id_obj = host.with_frame(Frame::TestContract(parent), || {
+        let wasm = host.test_bin_obj(&[0; 4])?.to_object();
+        let salt = host.test_bin_obj(&[1; 32])?.to_object();
+        let args = host.vec_new(().into())?;
+        host.create_and_init_contract(wasm, salt, Symbol::from_str("init"), args)
+    })?;
+    assert_eq!(host.hash_from_obj_input("id", id_obj)?, id);
+    Ok(())
+}
@@ -1,8 +1,8 @@
 use crate::{
-    events::HostEvent,
+    events::{DebugArg, DebugEvent, Events, HostEvent},
     xdr::{
         ContractEvent, ContractEventBody, ContractEventType, ContractEventV0, ExtensionPoint, Hash,
-        ScMap, ScMapEntry, ScObject::Map, ScVal,
+        ReadXdr, ScMap, ScMapEntry, ScObject::Map, ScVal, ScVmErrorCode, VecM, WriteXdr,
     },
     ContractFunctionSet, Env, EnvBase, Host, HostError, RawVal, Symbol, OK,
 };
@@ -65,3 +65,197 @@ fn contract_event() -> Result<(), HostError> {
     };
     Ok(())
 }
+
+pub struct FailingContractWithEvents;
+
+impl ContractFunctionSet for FailingContractWithEvents {
+    fn call(&self, _func: &Symbol, host: &Host, _args: &[RawVal]) -> Option<RawVal> {
+        let mut topics = host.vec_new(().into());
+        topics = host.vec_push(topics, 0u32.into());
+        host.contract_event(topics, 1u32.into());
+        // Returning None makes the call fail after the event was recorded.
+        None
+    }
+}
+
+#[test]
+fn failed_try_call_rolls_back_contract_events() -> Result<(), HostError> {
+    let host = Host::default();
+    let dummy_id = [0; 32];
+    let mut id = host.binary_new();
+    id = host.binary_copy_from_slice(id, 0u32.into(), &dummy_id);
+    let test_contract = Rc::new(FailingContractWithEvents {});
+    let sym = Symbol::from_str("fail");
+    let args = host.test_vec_obj::<i32>(&[])?;
+    host.register_test_contract(id, test_contract)?;
+    let res = host.try_call(id, sym.into(), args.into());
+    assert_ne!(res.get_payload(), OK.to_raw().get_payload());
+
+    // The contract event is gone but the debug event from `try_call` remains.
+    let events = host.get_events()?;
+    assert!(!events.0.iter().any(|e| matches!(e, HostEvent::Contract(_))));
+    assert!(events.0.iter().any(|e| matches!(e, HostEvent::Debug(_))));
+    Ok(())
+}
+
+#[test]
+fn failed_try_call_records_callee_and_status() -> Result<(), HostError> {
+    let host = Host::default();
+    let mut id = host.binary_new();
+    id = host.binary_copy_from_slice(id, 0u32.into(), &[1; 32]);
+    let sym = Symbol::from_str("fail");
+    let args = host.test_vec_obj::<i32>(&[])?;
+    host.register_test_contract(id, Rc::new(FailingContractWithEvents {}))?;
+    let status = host.try_call(id, sym.into(), args.into());
+
+    let events = host.get_events()?;
+    match events.0.last() {
+        Some(HostEvent::Debug(de)) => {
+            assert_eq!(de.args.len(), 3);
+            match (&de.args[0], &de.args[1], &de.args[2]) {
+                (DebugArg::Val(c), DebugArg::Val(f), DebugArg::Val(s)) => {
+                    assert_eq!(c.get_payload(), id.to_raw().get_payload());
+                    assert_eq!(f.get_payload(), sym.to_raw().get_payload());
+                    assert_eq!(s.get_payload(), status.get_payload());
+                }
+                _ => panic!("unexpected debug args"),
+            }
+        }
+        _ => panic!("missing try_call debug event"),
+    }
+    Ok(())
+}
+
+#[test]
+fn total_event_bytes_sums_contract_events() -> Result<(), HostError> {
+    let host = Host::default();
+    let dummy_id = [0; 32];
+    let mut id = host.binary_new();
+    id = host.binary_copy_from_slice(id, 0u32.into(), &dummy_id);
+    host.register_test_contract(id, Rc::new(EmptyContractWithEvents {}))?;
+    assert_eq!(host.total_event_bytes()?, 0);
+
+    let args = host.test_vec_obj::<i32>(&[])?;
+    for _ in 0..2 {
+        host.call(id, Symbol::from_str("add"), args.clone().into());
+    }
+    let event_ref = ContractEvent {
+        ext: ExtensionPoint::V0,
+        contract_id: Some(Hash(dummy_id)),
+        type_: ContractEventType::Contract,
+        body: ContractEventBody::V0(ContractEventV0 {
+            topics: host.map_err(vec![ScVal::U32(0), ScVal::U32(1)].try_into())?,
+            data: ScVal::Object(Some(Map(host.map_err(ScMap::try_from(vec![
+                ScMapEntry {
+                    key: ScVal::U32(1),
+                    val: ScVal::U32(2),
+                },
+            ]))?))),
+        }),
+    };
+    let mut buf = Vec::new();
+    host.map_err(event_ref.write_xdr(&mut buf))?;
+    assert_eq!(host.total_event_bytes()?, 2 * buf.len() as u64);
+
+    // Debug events are not counted.
+    host.record_debug_event(DebugEvent::new().msg("not counted"))?;
+    assert_eq!(host.total_event_bytes()?, 2 * buf.len() as u64);
+    Ok(())
+}
+
+#[test]
+fn write_contract_events_xdr_streams_event_array() -> Result<(), HostError> {
+    let host = Host::default();
+    let mut events = Events::default();
+    let mut expected = Vec::new();
+    for i in 0..3 {
+        let ce = ContractEvent {
+            ext: ExtensionPoint::V0,
+            contract_id: Some(Hash([i as u8; 32])),
+            type_: ContractEventType::Contract,
+            body: ContractEventBody::V0(ContractEventV0 {
+                topics: host.map_err(vec![ScVal::U32(i)].try_into())?,
+                data: ScVal::U32(i + 1),
+            }),
+        };
+        events.record_contract_event(ce.clone());
+        events.record_debug_event(DebugEvent::new().msg("not written"));
+        expected.push(ce);
+    }
+
+    let mut buf = Vec::new();
+    host.map_err(events.write_contract_events_xdr(&mut buf))?;
+
+    // The stream matches encoding the whole sequence at once.
+    let expected: VecM<ContractEvent> = host.map_err(expected.try_into())?;
+    let mut all_at_once = Vec::new();
+    host.map_err(expected.write_xdr(&mut all_at_once))?;
+    assert_eq!(buf, all_at_once);
+    let decoded = host.map_err(VecM::<ContractEvent>::read_xdr(&mut &buf[..]))?;
+    assert_eq!(decoded, expected);
+    Ok(())
+}
+
+#[test]
+fn try_call_reraises_unrecoverable_errors() -> Result<(), HostError> {
+    let host = Host::default();
+    let id = host.test_bin_obj(&[2; 32])?.to_object();
+    host.register_test_contract(id, Rc::new(FailingContractWithEvents {}))?;
+    let sym = Symbol::from_str("fail");
+    let args = host.test_vec_obj::<i32>(&[])?.to_object();
+
+    // A contract failure is recoverable and comes back as a status.
+    let err = crate::CheckedEnv::call(&host, id, sym, args).unwrap_err();
+    assert!(err.is_recoverable());
+    let status = crate::CheckedEnv::try_call(&host, id, sym, args)?;
+    assert_eq!(status.get_payload(), err.status.to_raw().get_payload());
+
+    // Running out of budget is not, and escapes `try_call`.
+    host.get_budget(|budget| budget.reset_limits(0, 0));
+    let res = crate::CheckedEnv::try_call(&host, id, sym, args);
+    let code = ScVmErrorCode::TrapMemLimitExceeded;
+    assert!(!HostError::from(code).is_recoverable());
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
+
+#[test]
+fn contract_events_root_ignores_debug_events() -> Result<(), HostError> {
+    let host = Host::default();
+    let event = |i: u32| -> Result<ContractEvent, HostError> {
+        Ok(ContractEvent {
+            ext: ExtensionPoint::V0,
+            contract_id: Some(Hash([0; 32])),
+            type_: ContractEventType::Contract,
+            body: ContractEventBody::V0(ContractEventV0 {
+                topics: host.map_err(vec![ScVal::U32(i)].try_into())?,
+                data: ScVal::U32(i),
+            }),
+        })
+    };
+    let mut plain = Events::default();
+    let mut with_debug = Events::default();
+    for i in 0..3 {
+        plain.record_contract_event(event(i)?);
+        with_debug.record_debug_event(DebugEvent::new().msg("between"));
+        with_debug.record_contract_event(event(i)?);
+    }
+    with_debug.record_debug_event(DebugEvent::new().msg("after"));
+
+    assert_eq!(
+        plain.canonical_contract_events(),
+        with_debug.canonical_contract_events()
+    );
+    let root = host.map_err(plain.contract_events_root())?;
+    assert_eq!(root, host.map_err(with_debug.contract_events_root())?);
+
+    with_debug.record_contract_event(event(3)?);
+    assert_ne!(root, host.map_err(with_debug.contract_events_root())?);
+    // Order matters too.
+    let mut reversed = Events::default();
+    for ce in plain.canonical_contract_events().into_iter().rev() {
+        reversed.record_contract_event(ce);
+    }
+    assert_ne!(root, host.map_err(reversed.contract_events_root())?);
+    Ok(())
+}
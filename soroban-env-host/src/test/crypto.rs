@@ -1,8 +1,11 @@
 use crate::{
-    xdr::{ScObject, ScVal},
-    CheckedEnv, Host, HostError,
+    host::{SIG_SCHEME_ED25519, SIG_SCHEME_SECP256K1},
+    xdr::{ScHostFnErrorCode, ScHostObjErrorCode, ScObject, ScVal},
+    CheckedEnv, Host, HostError, RawVal,
 };
+use ed25519_dalek::{Keypair, SecretKey, Signer as Ed25519Signer};
 use hex::FromHex;
+use k256::ecdsa::{signature::Signer, Signature, SigningKey};
 
 /// crypto tests
 #[test]
@@ -32,6 +35,88 @@ fn sha256_test() -> Result<(), HostError> {
     Ok(())
 }
 
+#[test]
+fn sha256_parts_test() -> Result<(), HostError> {
+    let host = Host::default();
+    let mut parts = host.vec_new(().into())?;
+    for part in [&b"create"[..], &[1; 32], &[], b"salt"] {
+        parts = host.vec_push(parts, host.test_bin_obj(part)?.to_raw())?;
+    }
+    let whole = host.test_bin_obj(&[&b"create"[..], &[1; 32], b"salt"].concat())?;
+    let res = host.compute_hash_sha256_parts(parts)?;
+    let exp = host.compute_hash_sha256(whole.to_object())?;
+    assert_eq!(host.obj_cmp(res.into(), exp.into())?, 0);
+
+    let empty = host.vec_new(().into())?;
+    let res = host.compute_hash_sha256_parts(empty)?;
+    let exp = host.compute_hash_sha256(host.test_bin_obj(&[])?.to_object())?;
+    assert_eq!(host.obj_cmp(res.into(), exp.into())?, 0);
+    Ok(())
+}
+
+#[test]
+fn id_preimage_hash_streams_parts() -> Result<(), HostError> {
+    let host = Host::default();
+    // The sha256 of "abc", fed in two parts.
+    let hash = host.compute_id_preimage_hash(&[b"a", b"bc"])?;
+    let exp = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    assert_eq!(hash, Vec::from_hex(exp).unwrap());
+    Ok(())
+}
+
+#[test]
+fn keccak256_test() -> Result<(), HostError> {
+    let host = Host::default();
+    let cases: [(&[u8], &str); 2] = [
+        (
+            b"",
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+        ),
+        (
+            b"abc",
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+        ),
+    ];
+    for (input, exp) in cases {
+        let obj = host.test_bin_obj(input)?;
+        let hash_obj = host.compute_hash_keccak256(obj.to_object())?;
+        let bin = match host.from_host_obj(hash_obj)? {
+            ScObject::Bytes(bin) => bin,
+            _ => panic!("Wrong type"),
+        };
+        assert_eq!(bin.as_vec().clone(), Vec::from_hex(exp).unwrap());
+    }
+    Ok(())
+}
+
+#[test]
+fn hmac_sha256_test() -> Result<(), HostError> {
+    let host = Host::default();
+    // Test cases 2 and 6 of RFC 4231; the latter has a key longer than a block.
+    let cases: [(&[u8], &[u8], &str); 2] = [
+        (
+            b"Jefe",
+            b"what do ya want for nothing?",
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        ),
+        (
+            &[0xaa; 131],
+            b"Test Using Larger Than Block-Size Key - Hash Key First",
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+        ),
+    ];
+    for (key, msg, exp) in cases {
+        let key = host.test_bin_obj(key)?.to_object();
+        let msg = host.test_bin_obj(msg)?.to_object();
+        let mac = match host.from_host_obj(host.compute_hmac_sha256(key, msg)?)? {
+            ScObject::Bytes(bin) => bin,
+            _ => panic!("Wrong type"),
+        };
+        assert_eq!(mac.as_vec().clone(), Vec::from_hex(exp).unwrap());
+    }
+    Ok(())
+}
+
 #[test]
 fn ed25519_verify_test() -> Result<(), HostError> {
     let host = Host::default();
@@ -76,3 +161,183 @@ fn ed25519_verify_test() -> Result<(), HostError> {
     };
     Ok(())
 }
+
+#[test]
+fn verify_sig_dispatches_on_scheme() -> Result<(), HostError> {
+    let host = Host::default();
+
+    // ed25519, using the RFC 8032 vector from `ed25519_verify_test`.
+    let pub_bytes: Vec<u8> =
+        FromHex::from_hex(b"3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c")
+            .unwrap();
+    let sig_bytes: Vec<u8> = FromHex::from_hex(b"92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00").unwrap();
+    let obj_pub = host.test_bin_obj(&pub_bytes)?.to_object();
+    let obj_msg = host.test_bin_obj(&[0x72])?.to_object();
+    let obj_sig = host.test_bin_obj(&sig_bytes)?.to_object();
+    host.verify_sig(SIG_SCHEME_ED25519.into(), obj_msg, obj_pub, obj_sig)?;
+
+    // secp256k1
+    let signing_key = SigningKey::from_bytes(&[1; 32]).unwrap();
+    let msg = b"hello soroban";
+    let sig: Signature = signing_key.sign(msg);
+    let obj_pub = host
+        .test_bin_obj(signing_key.verifying_key().to_bytes().as_slice())?
+        .to_object();
+    let obj_msg = host.test_bin_obj(msg)?.to_object();
+    let obj_sig = host.test_bin_obj(sig.as_ref())?.to_object();
+    host.verify_sig(SIG_SCHEME_SECP256K1.into(), obj_msg, obj_pub, obj_sig)?;
+
+    // A tampered message fails.
+    let obj_msg2 = host.test_bin_obj(b"hello soroban!")?.to_object();
+    assert!(host
+        .verify_sig(SIG_SCHEME_SECP256K1.into(), obj_msg2, obj_pub, obj_sig)
+        .is_err());
+
+    // An unknown scheme is rejected before any verification.
+    let res = host.verify_sig(7_u32.into(), obj_msg, obj_pub, obj_sig);
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostFnErrorCode::InputArgsInvalid
+    ));
+    Ok(())
+}
+
+fn ed25519_keypair(seed: u8) -> Keypair {
+    let secret = SecretKey::from_bytes(&[seed; 32]).unwrap();
+    let public = (&secret).into();
+    Keypair { secret, public }
+}
+
+// Verifies `msg` against signatures by the keypairs seeded by `signers`, with
+// `None` leaving a slot unsigned.
+fn verify_threshold(
+    host: &Host,
+    msg: &[u8],
+    signers: &[(u8, Option<u8>)],
+    threshold: u32,
+) -> Result<bool, HostError> {
+    let mut keys = host.vec_new(().into())?;
+    let mut sigs = host.vec_new(().into())?;
+    for (key_seed, signer_seed) in signers {
+        let public = ed25519_keypair(*key_seed).public;
+        keys = host.vec_push(keys, host.test_bin_obj(public.as_bytes())?.to_raw())?;
+        let sig = match signer_seed {
+            Some(seed) => {
+                let sig = Ed25519Signer::sign(&ed25519_keypair(*seed), msg);
+                host.test_bin_obj(&sig.to_bytes())?.to_raw()
+            }
+            None => ().into(),
+        };
+        sigs = host.vec_push(sigs, sig)?;
+    }
+    let msg = host.test_bin_obj(msg)?.to_object();
+    let res = host.verify_threshold_ed25519(msg, keys, sigs, threshold.into())?;
+    Ok(bool::try_from(res)?)
+}
+
+#[test]
+fn ed25519_threshold_test() -> Result<(), HostError> {
+    let host = Host::default();
+    let msg = b"multisig";
+    // Two valid signatures and one unsigned slot.
+    let signers = [(1, Some(1)), (2, None), (3, Some(3))];
+    assert!(verify_threshold(&host, msg, &signers, 1)?);
+    assert!(verify_threshold(&host, msg, &signers, 2)?);
+    assert!(!verify_threshold(&host, msg, &signers, 3)?);
+
+    // A signature by the wrong key does not count.
+    let signers = [(1, Some(1)), (2, Some(4))];
+    assert!(!verify_threshold(&host, msg, &signers, 2)?);
+
+    // A malformed trailing slot traps even though the threshold is already
+    // met by the slots before it.
+    let keypair = ed25519_keypair(1);
+    let sig = Ed25519Signer::sign(&keypair, msg);
+    let mut keys = host.vec_new(().into())?;
+    let mut sigs = host.vec_new(().into())?;
+    for _ in 0..2 {
+        keys = host.vec_push(keys, host.test_bin_obj(keypair.public.as_bytes())?.to_raw())?;
+    }
+    sigs = host.vec_push(sigs, host.test_bin_obj(&sig.to_bytes())?.to_raw())?;
+    sigs = host.vec_push(sigs, host.test_bin_obj(&[0; 3])?.to_raw())?;
+    let msg_obj = host.test_bin_obj(msg)?.to_object();
+    let res = host.verify_threshold_ed25519(msg_obj, keys, sigs, 1_u32.into());
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostObjErrorCode::ContractHashWrongLength
+    ));
+
+    let keys = host.test_vec_obj::<u32>(&[])?.to_object();
+    let sigs = host.vec_push(host.vec_new(().into())?, ().into())?;
+    let msg = host.test_bin_obj(msg)?.to_object();
+    let res = host.verify_threshold_ed25519(msg, keys, sigs, 0_u32.into());
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostFnErrorCode::InputArgsWrongLength
+    ));
+    Ok(())
+}
+
+// Verifies a batch in which the keypair seeded by each signer signs the
+// message at the same index.
+fn verify_batch(host: &Host, msgs: &[&[u8]], signers: &[u8]) -> Result<RawVal, HostError> {
+    let mut msg_vec = host.vec_new(().into())?;
+    let mut keys = host.vec_new(().into())?;
+    let mut sigs = host.vec_new(().into())?;
+    for (msg, seed) in msgs.iter().zip(signers.iter()) {
+        let keypair = ed25519_keypair(*seed);
+        let sig = Ed25519Signer::sign(&keypair, msg);
+        msg_vec = host.vec_push(msg_vec, host.test_bin_obj(msg)?.to_raw())?;
+        keys = host.vec_push(keys, host.test_bin_obj(keypair.public.as_bytes())?.to_raw())?;
+        sigs = host.vec_push(sigs, host.test_bin_obj(&sig.to_bytes())?.to_raw())?;
+    }
+    host.verify_sig_ed25519_batch(msg_vec, keys, sigs)
+}
+
+#[test]
+fn ed25519_batch_verify_test() -> Result<(), HostError> {
+    let host = Host::default();
+    let msgs: [&[u8]; 3] = [b"one", b"two", b"three"];
+    let res = verify_batch(&host, &msgs, &[1, 2, 3])?;
+    assert!(bool::try_from(res)?);
+
+    // Swapping two messages breaks two of the signatures.
+    let swapped: [&[u8]; 3] = [b"two", b"one", b"three"];
+    let res = verify_batch(&host, &swapped, &[1, 2, 3]);
+    assert!(res.is_err());
+
+    let msgs = host.test_vec_obj::<u32>(&[])?.to_object();
+    let keys = host.vec_push(host.vec_new(().into())?, ().into())?;
+    let res = host.verify_sig_ed25519_batch(msgs, keys, keys);
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostFnErrorCode::InputArgsWrongLength
+    ));
+    Ok(())
+}
+
+fn derive(host: &Host, parent: &[u8], index: u32) -> Result<Vec<u8>, HostError> {
+    let parent = host.test_bin_obj(parent)?.to_object();
+    let child = host.derive_identifier(parent, index.into())?;
+    match host.from_host_obj(child)? {
+        ScObject::Bytes(b) => Ok(b.as_vec().clone()),
+        _ => panic!("Wrong type"),
+    }
+}
+
+#[test]
+fn derive_identifier_test() -> Result<(), HostError> {
+    let host = Host::default();
+    let child = derive(&host, &[1; 32], 0)?;
+    assert_eq!(child.len(), 32);
+    assert_eq!(child, derive(&host, &[1; 32], 0)?);
+    assert_ne!(child, derive(&host, &[1; 32], 1)?);
+    assert_ne!(child, derive(&host, &[2; 32], 0)?);
+
+    let res = host.derive_identifier(host.test_bin_obj(&[1; 16])?.to_object(), 0_u32.into());
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostObjErrorCode::ContractHashWrongLength
+    ));
+    Ok(())
+}
@@ -3,7 +3,7 @@ use soroban_env_common::{xdr::ScVmErrorCode, RawVal};
 use crate::{
     budget::Budget,
     vm::Vm,
-    xdr::{Hash, ScHostObjErrorCode, ScVal, ScVec},
+    xdr::{Hash, ScContractCode, ScHostObjErrorCode, ScVal, ScVec},
     CheckedEnv, Host, HostError, Status, Symbol, Tag,
 };
 use soroban_test_wasms::{ADD_I32, INVOKE_CONTRACT, VEC};
@@ -32,6 +32,20 @@ fn invoke_single_contract_function() -> Result<(), HostError> {
     Ok(())
 }
 
+#[test]
+fn invoke_function_with_non_symbol_name() -> Result<(), HostError> {
+    // Re-export `add` under a name that is not a valid symbol.
+    let mut wasm = ADD_I32.to_vec();
+    let pos = wasm.windows(5).position(|w| w == b"\x03add\x00").unwrap();
+    wasm[pos + 1..pos + 4].copy_from_slice(b"a-d");
+    let host = Host::default();
+    let vm = Vm::new(&host, [0; 32].into(), &wasm)?;
+    let scvec0: ScVec = host.test_scvec::<i32>(&[4, 7])?;
+    let res = vm.invoke_function(&host, "a-d", &scvec0)?;
+    assert_eq!(res, ScVal::I32(11));
+    Ok(())
+}
+
 #[test]
 fn invoke_cross_contract() -> Result<(), HostError> {
     let dummy_id = [0; 32];
@@ -124,3 +138,25 @@ fn invoke_cross_contract_indirect_err() -> Result<(), HostError> {
     assert_eq!(status.get_payload(), exp.to_raw().get_payload());
     Ok(())
 }
+
+#[test]
+fn create_contract_validates_wasm_when_enabled() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.set_validate_wasm_on_create(true);
+    let junk: &[u8] = b"not a wasm module";
+
+    let bad_id = host.test_bin_obj(&[1; 32])?.to_object();
+    let res = host.create_contract_with_id(ScContractCode::Wasm(junk.try_into().unwrap()), bad_id);
+    assert!(res.is_err());
+    let bad_key = host.contract_code_ledger_key(Hash([1; 32]));
+    assert!(!host.visit_storage(|storage| storage.has(&bad_key))?);
+
+    let good_id = host.test_bin_obj(&[2; 32])?.to_object();
+    host.create_contract_with_id(ScContractCode::Wasm(ADD_I32.try_into().unwrap()), good_id)?;
+
+    // Without validation, the junk is stored as before.
+    host.set_validate_wasm_on_create(false);
+    host.create_contract_with_id(ScContractCode::Wasm(junk.try_into().unwrap()), bad_id)?;
+    assert!(host.visit_storage(|storage| storage.has(&bad_key))?);
+    Ok(())
+}
@@ -0,0 +1,196 @@
+use soroban_env_common::xdr::{
+    Hash, LedgerKey, LedgerKeyContractData, ScHostFnErrorCode, ScHostStorageErrorCode, ScVal,
+};
+
+use crate::{
+    host::{Frame, LedgerInfo},
+    storage::Storage,
+    test::util::EmptySnapshot,
+    CheckedEnv, Host, HostError, Object, RawVal,
+};
+use std::rc::Rc;
+
+fn test_ledger_info(sequence_number: u32) -> LedgerInfo {
+    LedgerInfo {
+        protocol_version: 1,
+        sequence_number,
+        timestamp: 0,
+        network_id: vec![7; 32],
+        base_reserve: 5_000_000,
+        base_fee: 100,
+    }
+}
+
+fn random_index_in(host: &Host, id: u8, upper: RawVal) -> Result<RawVal, HostError> {
+    host.with_frame(Frame::Token(Hash([id; 32])), || host.random_index(upper))
+}
+
+#[test]
+fn random_index_deterministic_within_ledger() -> Result<(), HostError> {
+    let host = Host::default();
+    host.set_ledger_info(test_ledger_info(1))?;
+    let a = random_index_in(&host, 0, u32::MAX.into())?;
+    let b = random_index_in(&host, 0, u32::MAX.into())?;
+    assert_eq!(a.get_payload(), b.get_payload());
+    let small = u32::try_from(random_index_in(&host, 0, 10_u32.into())?)?;
+    assert!(small < 10);
+    Ok(())
+}
+
+#[test]
+fn random_index_varies_across_ledgers_and_contracts() -> Result<(), HostError> {
+    let host = Host::default();
+    host.set_ledger_info(test_ledger_info(1))?;
+    let a = random_index_in(&host, 0, u32::MAX.into())?;
+    let b = random_index_in(&host, 1, u32::MAX.into())?;
+    assert_ne!(a.get_payload(), b.get_payload());
+    host.set_ledger_info(test_ledger_info(2))?;
+    let c = random_index_in(&host, 0, u32::MAX.into())?;
+    assert_ne!(a.get_payload(), c.get_payload());
+    Ok(())
+}
+
+#[test]
+fn random_index_zero_upper() -> Result<(), HostError> {
+    let host = Host::default();
+    host.set_ledger_info(test_ledger_info(1))?;
+    let res = random_index_in(&host, 0, 0_u32.into());
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostFnErrorCode::InputArgsInvalid
+    ));
+    Ok(())
+}
+
+#[test]
+fn ledger_timestamp_small() -> Result<(), HostError> {
+    let host = Host::default();
+    let mut li = test_ledger_info(1);
+    li.timestamp = 1_660_000_000;
+    host.set_ledger_info(li.clone())?;
+    let ts = host.get_ledger_timestamp_small()?;
+    assert!(ts.is_u63());
+    assert_eq!(unsafe { ts.unchecked_as_u63() }, 1_660_000_000);
+
+    // A timestamp beyond the u63 range falls back to a u64 object.
+    li.timestamp = u64::MAX;
+    host.set_ledger_info(li)?;
+    let ts = host.get_ledger_timestamp_small()?;
+    let obj: Object = ts.try_into()?;
+    assert_eq!(host.obj_to_u64(obj)?, u64::MAX);
+    Ok(())
+}
+
+#[test]
+fn ledger_base_reserve_and_fee() -> Result<(), HostError> {
+    let host = Host::default();
+    host.set_ledger_info(test_ledger_info(1))?;
+    let reserve = u32::try_from(host.get_ledger_base_reserve()?)?;
+    assert_eq!(reserve, 5_000_000);
+    let fee = u32::try_from(host.get_ledger_base_fee()?)?;
+    assert_eq!(fee, 100);
+    Ok(())
+}
+
+#[test]
+fn set_ledger_info_rejects_short_network_id() -> Result<(), HostError> {
+    let host = Host::default();
+    let mut li = test_ledger_info(1);
+    li.network_id = vec![7; 16];
+    let res = host.set_ledger_info(li);
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostFnErrorCode::InputArgsWrongLength
+    ));
+    // Nothing was stored.
+    assert!(host.get_ledger_network_id().is_err());
+    Ok(())
+}
+
+#[test]
+fn contract_data_expires_after_expire_seq() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.set_ledger_info(test_ledger_info(10))?;
+    host.with_frame(Frame::Token(Hash([0; 32])), || {
+        host.put_contract_data_with_expiration(1_u32.into(), 1_u32.into(), 11_u32.into())?;
+        host.put_contract_data(2_u32.into(), 2_u32.into())?;
+        Ok(())
+    })?;
+    // Still live up to and including the expiration ledger.
+    host.set_ledger_info(test_ledger_info(11))?;
+    host.with_frame(Frame::Token(Hash([0; 32])), || {
+        assert!(bool::try_from(host.has_contract_data(1_u32.into())?)?);
+        assert_eq!(u32::try_from(host.get_contract_data(1_u32.into())?)?, 1);
+        Ok(())
+    })?;
+    host.set_ledger_info(test_ledger_info(12))?;
+    host.with_frame(Frame::Token(Hash([0; 32])), || {
+        assert!(!bool::try_from(host.has_contract_data(1_u32.into())?)?);
+        let res = host.get_contract_data(1_u32.into());
+        assert!(HostError::result_matches_err_status(
+            res,
+            ScHostStorageErrorCode::MissingKeyInGet
+        ));
+        // Entries without an expiration are unaffected.
+        assert_eq!(u32::try_from(host.get_contract_data(2_u32.into())?)?, 2);
+        // Expired keys are not listed.
+        let keys = host.get_contract_data_keys()?;
+        let expected = host.test_vec_obj::<u32>(&[2])?;
+        assert_eq!(host.obj_cmp(keys.into(), expected.to_raw())?, 0);
+        // Rewriting the key clears its expiration.
+        host.put_contract_data(1_u32.into(), 3_u32.into())?;
+        assert_eq!(u32::try_from(host.get_contract_data(1_u32.into())?)?, 3);
+        Ok(())
+    })
+}
+
+#[test]
+fn destroy_current_contract_deletes_expired_data() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.set_ledger_info(test_ledger_info(10))?;
+    host.with_frame(Frame::Token(Hash([0; 32])), || {
+        host.put_contract_data_with_expiration(1_u32.into(), 1_u32.into(), 11_u32.into())?;
+        host.put_contract_data(2_u32.into(), 2_u32.into())
+    })?;
+    host.set_ledger_info(test_ledger_info(12))?;
+    // The expired entry is still stored, so it is deleted along with the
+    // live one rather than left behind.
+    let removed = host.with_frame(Frame::Token(Hash([0; 32])), || {
+        host.destroy_current_contract()
+    })?;
+    assert_eq!(u32::try_from(removed)?, 2);
+    let key = LedgerKey::ContractData(LedgerKeyContractData {
+        contract_id: Hash([0; 32]),
+        key: ScVal::U32(1),
+    });
+    assert!(!host.visit_storage(|storage| storage.has(&key))?);
+    assert_eq!(
+        host.visit_storage(|storage| Ok(storage.expiration(&key)))?,
+        None
+    );
+    Ok(())
+}
+
+#[test]
+fn put_contract_data_records_ledger_sequence() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.set_ledger_info(test_ledger_info(42))?;
+    host.with_frame(Frame::Token(Hash([0; 32])), || {
+        host.put_contract_data(1_u32.into(), 1_u32.into())
+    })?;
+    let key = LedgerKey::ContractData(LedgerKeyContractData {
+        contract_id: Hash([0; 32]),
+        key: ScVal::U32(1),
+    });
+    let entry = host.visit_storage(|storage| storage.get(&key))?;
+    assert_eq!(entry.last_modified_ledger_seq, 42);
+
+    // Without ledger info there is no sequence number to record.
+    let storage = Storage::with_recording_footprint(Rc::new(EmptySnapshot));
+    let host = Host::with_storage(storage);
+    let res = host.with_frame(Frame::Token(Hash([0; 32])), || {
+        host.put_contract_data(1_u32.into(), 1_u32.into())
+    });
+    assert!(res.is_err());
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use crate::{
-    xdr::{ScMap, ScMapEntry, ScObject, ScVal, ScVec},
-    CheckedEnv, Host, HostError, RawVal, RawValConvertible, Symbol, UNKNOWN_ERROR,
+    xdr::{ScHostFnErrorCode, ScMap, ScMapEntry, ScObject, ScVal, ScVec},
+    CheckedEnv, Host, HostError, Object, RawVal, RawValConvertible, Symbol, UNKNOWN_ERROR,
 };
 
 #[test]
@@ -189,3 +189,261 @@ fn map_prev_and_next_heterogeneous() -> Result<(), HostError> {
 
     Ok(())
 }
+
+#[test]
+fn map_key_intersection_count() -> Result<(), HostError> {
+    let host = Host::default();
+    let mut a = host.map_new()?;
+    a = host.map_put(a, 1_u32.into(), 10_u32.into())?;
+    a = host.map_put(a, 2_u32.into(), 20_u32.into())?;
+    a = host.map_put(a, 3_u32.into(), 30_u32.into())?;
+
+    let mut b = host.map_new()?;
+    b = host.map_put(b, 4_u32.into(), 40_u32.into())?;
+    b = host.map_put(b, 5_u32.into(), 50_u32.into())?;
+
+    let mut c = host.map_new()?;
+    c = host.map_put(c, 2_u32.into(), 0_u32.into())?;
+    c = host.map_put(c, 3_u32.into(), 0_u32.into())?;
+    c = host.map_put(c, 4_u32.into(), 0_u32.into())?;
+    c = host.map_put(c, 5_u32.into(), 0_u32.into())?;
+
+    // disjoint
+    let n = host.map_key_intersection_count(a, b)?;
+    assert_eq!(n.get_payload(), RawVal::from_u32(0).get_payload());
+    // identical
+    let n = host.map_key_intersection_count(a, a)?;
+    assert_eq!(n.get_payload(), RawVal::from_u32(3).get_payload());
+    // partial overlap, in both argument orders
+    let n = host.map_key_intersection_count(a, c)?;
+    assert_eq!(n.get_payload(), RawVal::from_u32(2).get_payload());
+    let n = host.map_key_intersection_count(c, a)?;
+    assert_eq!(n.get_payload(), RawVal::from_u32(2).get_payload());
+    Ok(())
+}
+
+#[test]
+fn map_remove_default_values() -> Result<(), HostError> {
+    let host = Host::default();
+    let mut balances = host.map_new()?;
+    balances = host.map_put(balances, 1_u32.into(), 0_u32.into())?;
+    balances = host.map_put(balances, 2_u32.into(), 5_u32.into())?;
+    balances = host.map_put(balances, 3_u32.into(), 0_u32.into())?;
+
+    let compact = host.map_remove_default_values(balances, 0_u32.into())?;
+    assert_eq!(
+        host.map_len(compact)?.get_payload(),
+        RawVal::from_u32(1).get_payload()
+    );
+    assert!(bool::try_from(host.map_has(compact, 2_u32.into())?)?);
+    assert!(!bool::try_from(host.map_has(compact, 1_u32.into())?)?);
+    assert!(!bool::try_from(host.map_has(compact, 3_u32.into())?)?);
+    // The input map is left as it was.
+    assert_eq!(
+        host.map_len(balances)?.get_payload(),
+        RawVal::from_u32(3).get_payload()
+    );
+
+    // A map without any default-valued entries is unchanged.
+    let same = host.map_remove_default_values(compact, 0_u32.into())?;
+    assert_eq!(host.obj_cmp(same.into(), compact.into())?, 0);
+    Ok(())
+}
+
+#[test]
+fn map_min_and_max_value() -> Result<(), HostError> {
+    let host = Host::default();
+    let mut bids = host.map_new()?;
+    bids = host.map_put(bids, 1_u32.into(), 30_u32.into())?;
+    bids = host.map_put(bids, 2_u32.into(), 10_u32.into())?;
+    bids = host.map_put(bids, 3_u32.into(), 20_u32.into())?;
+
+    let min = host.map_min_value(bids)?;
+    assert_eq!(min.get_payload(), RawVal::from_u32(10).get_payload());
+    let max = host.map_max_value(bids)?;
+    assert_eq!(max.get_payload(), RawVal::from_u32(30).get_payload());
+
+    // An empty map yields void.
+    let empty = host.map_new()?;
+    assert!(host.map_min_value(empty)?.is_void());
+    assert!(host.map_max_value(empty)?.is_void());
+    Ok(())
+}
+
+#[test]
+fn map_argmin_and_argmax() -> Result<(), HostError> {
+    let host = Host::default();
+    let mut scores = host.map_new()?;
+    scores = host.map_put(scores, 1_u32.into(), 30_u32.into())?;
+    scores = host.map_put(scores, 2_u32.into(), 10_u32.into())?;
+    scores = host.map_put(scores, 3_u32.into(), 50_u32.into())?;
+    scores = host.map_put(scores, 4_u32.into(), 20_u32.into())?;
+
+    let loser = host.map_argmin(scores)?;
+    assert_eq!(loser.get_payload(), RawVal::from_u32(2).get_payload());
+    let winner = host.map_argmax(scores)?;
+    assert_eq!(winner.get_payload(), RawVal::from_u32(3).get_payload());
+
+    let empty = host.map_new()?;
+    assert!(host.map_argmin(empty)?.is_void());
+    assert!(host.map_argmax(empty)?.is_void());
+    Ok(())
+}
+
+#[test]
+fn map_argmin_and_argmax_ties() -> Result<(), HostError> {
+    let host = Host::default();
+    let mut scores = host.map_new()?;
+    scores = host.map_put(scores, 1_u32.into(), 10_u32.into())?;
+    scores = host.map_put(scores, 2_u32.into(), 50_u32.into())?;
+    scores = host.map_put(scores, 3_u32.into(), 10_u32.into())?;
+    scores = host.map_put(scores, 4_u32.into(), 50_u32.into())?;
+
+    // The first key in key order wins a tie.
+    let loser = host.map_argmin(scores)?;
+    assert_eq!(loser.get_payload(), RawVal::from_u32(1).get_payload());
+    let winner = host.map_argmax(scores)?;
+    assert_eq!(winner.get_payload(), RawVal::from_u32(2).get_payload());
+    Ok(())
+}
+
+#[test]
+fn map_obj_diff() -> Result<(), HostError> {
+    let host = Host::default();
+    let mut old = host.map_new()?;
+    old = host.map_put(old, 1_u32.into(), 10_u32.into())?;
+    old = host.map_put(old, 2_u32.into(), 20_u32.into())?;
+    old = host.map_put(old, 3_u32.into(), 30_u32.into())?;
+    let mut new = host.map_new()?;
+    new = host.map_put(new, 2_u32.into(), 25_u32.into())?;
+    new = host.map_put(new, 3_u32.into(), 30_u32.into())?;
+    new = host.map_put(new, 4_u32.into(), 40_u32.into())?;
+
+    let diff = host.obj_diff(old.into(), new.into())?;
+    let added: Object = host.vec_get(diff, 0_u32.into())?.try_into()?;
+    let removed: Object = host.vec_get(diff, 1_u32.into())?.try_into()?;
+    let changed: Object = host.vec_get(diff, 2_u32.into())?.try_into()?;
+
+    assert_eq!(u32::try_from(host.map_len(added)?)?, 1);
+    let v = host.map_get(added, 4_u32.into())?;
+    assert_eq!(v.get_payload(), RawVal::from_u32(40).get_payload());
+
+    assert_eq!(u32::try_from(host.vec_len(removed)?)?, 1);
+    let k = host.vec_get(removed, 0_u32.into())?;
+    assert_eq!(k.get_payload(), RawVal::from_u32(1).get_payload());
+
+    assert_eq!(u32::try_from(host.map_len(changed)?)?, 1);
+    let v = host.map_get(changed, 2_u32.into())?;
+    assert_eq!(v.get_payload(), RawVal::from_u32(25).get_payload());
+
+    // Diffing a map against a vec is a type error.
+    let vec = host.vec_new(().into())?;
+    assert!(host.obj_diff(old.into(), vec.into()).is_err());
+    Ok(())
+}
+
+#[test]
+fn map_obj_apply_diff_round_trip() -> Result<(), HostError> {
+    let host = Host::default();
+    let map_of = |entries: &[(u32, u32)]| -> Result<Object, HostError> {
+        let mut m = host.map_new()?;
+        for (k, v) in entries {
+            m = host.map_put(m, (*k).into(), (*v).into())?;
+        }
+        Ok(m)
+    };
+    let pairs = [
+        (map_of(&[(1, 10), (2, 20)])?, map_of(&[(2, 25), (3, 30)])?),
+        (map_of(&[])?, map_of(&[(1, 10)])?),
+        (map_of(&[(1, 10)])?, map_of(&[])?),
+        (map_of(&[(1, 10)])?, map_of(&[(1, 10)])?),
+    ];
+    for (old, new) in pairs {
+        let diff = host.obj_diff(old.into(), new.into())?;
+        let applied = host.obj_apply_diff(old.into(), diff)?;
+        assert_eq!(host.obj_cmp(applied, new.into())?, 0);
+    }
+
+    // A diff removing a key the base lacks is rejected.
+    let (old, new) = (map_of(&[(1, 10)])?, map_of(&[])?);
+    let diff = host.obj_diff(old.into(), new.into())?;
+    assert!(host.obj_apply_diff(new.into(), diff).is_err());
+    Ok(())
+}
+
+#[test]
+fn map_checksum_ignores_build_order() -> Result<(), HostError> {
+    let host = Host::default();
+    let build = |entries: &[(u32, u32)]| -> Result<Object, HostError> {
+        let mut m = host.map_new()?;
+        for (k, v) in entries {
+            m = host.map_put(m, (*k).into(), (*v).into())?;
+        }
+        Ok(m)
+    };
+    let a = build(&[(1, 10), (2, 20), (3, 30)])?;
+    let b = build(&[(3, 30), (1, 10), (2, 20)])?;
+    let c = build(&[(1, 10), (2, 21), (3, 30)])?;
+    let sum_a = host.map_checksum(a)?;
+    let sum_b = host.map_checksum(b)?;
+    let sum_c = host.map_checksum(c)?;
+    assert_eq!(host.obj_cmp(sum_a.into(), sum_b.into())?, 0);
+    assert_ne!(host.obj_cmp(sum_a.into(), sum_c.into())?, 0);
+    Ok(())
+}
+
+#[test]
+fn map_filter_keys_by_symbol_matches_symbols_and_tuples() -> Result<(), HostError> {
+    let host = Host::default();
+    let balance = Symbol::from_str("Balance");
+    let tuple = |s: Symbol| -> Result<RawVal, HostError> {
+        let v = host.vec_push(host.vec_new(().into())?, s.into())?;
+        Ok(host.vec_push(v, 7_u32.into())?.to_raw())
+    };
+    let balance_tuple = tuple(balance)?;
+    let entries: [(RawVal, u32); 5] = [
+        (balance.into(), 1),
+        (Symbol::from_str("Admin").into(), 2),
+        (balance_tuple, 3),
+        (tuple(Symbol::from_str("State"))?, 4),
+        (5_u32.into(), 5),
+    ];
+    let mut m = host.map_new()?;
+    for (k, v) in entries {
+        m = host.map_put(m, k, v.into())?;
+    }
+
+    let filtered = host.map_filter_keys_by_symbol(m, balance)?;
+    assert_eq!(u32::try_from(host.map_len(filtered)?)?, 2);
+    assert_eq!(u32::try_from(host.map_get(filtered, balance.into())?)?, 1);
+    assert_eq!(u32::try_from(host.map_get(filtered, balance_tuple)?)?, 3);
+    // The source map is untouched.
+    assert_eq!(u32::try_from(host.map_len(m)?)?, 5);
+    Ok(())
+}
+
+#[test]
+fn map_from_kv_vecs_builds_map() -> Result<(), HostError> {
+    let host = Host::default();
+    let keys = host.test_vec_obj::<u32>(&[3, 1, 2])?;
+    let vals = host.test_vec_obj::<u32>(&[30, 10, 20])?;
+    let m = host.map_from_kv_vecs(keys.to_object(), vals.to_object())?;
+    assert_eq!(u32::try_from(host.map_len(m)?)?, 3);
+    assert_eq!(u32::try_from(host.map_get(m, 1_u32.into())?)?, 10);
+    assert_eq!(u32::try_from(host.map_get(m, 3_u32.into())?)?, 30);
+
+    let short = host.test_vec_obj::<u32>(&[30, 10])?;
+    let res = host.map_from_kv_vecs(keys.to_object(), short.to_object());
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostFnErrorCode::InputArgsWrongLength
+    ));
+
+    let dup = host.test_vec_obj::<u32>(&[3, 1, 3])?;
+    let res = host.map_from_kv_vecs(dup.to_object(), vals.to_object());
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostFnErrorCode::InputArgsInvalid
+    ));
+    Ok(())
+}
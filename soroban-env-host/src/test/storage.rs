@@ -0,0 +1,467 @@
+use crate::{
+    budget::Budget,
+    host::{metered_map::MeteredOrdMap, Frame, CONTRACT_ALREADY_EXISTS, MAX_SCVAL_BINARY_LEN},
+    im_rc::OrdMap,
+    storage::{AccessType, Footprint, Storage, StorageDiffKind},
+    test::util::EmptySnapshot,
+    xdr::{
+        ContractDataEntry, Hash, LedgerEntry, LedgerEntryData, LedgerEntryExt, LedgerKey,
+        LedgerKeyContractData, ScContractCode, ScHostFnErrorCode, ScHostStorageErrorCode,
+        ScHostValErrorCode, ScMap, ScMapEntry, ScObject, ScVal, ScVec,
+    },
+    CheckedEnv, Host, HostError, Symbol,
+};
+
+use std::rc::Rc;
+
+const CONTRACT_ID: [u8; 32] = [0; 32];
+
+fn contract_data_key(k: u32) -> LedgerKey {
+    LedgerKey::ContractData(LedgerKeyContractData {
+        contract_id: Hash(CONTRACT_ID),
+        key: ScVal::U32(k),
+    })
+}
+
+// Builds a host whose footprint allows reading `read_only` keys, each holding
+// a value equal to its key, and writing `read_write` keys.
+fn host_with_contract_data(read_only: &[u32], read_write: &[u32]) -> Host {
+    let budget = Budget::default();
+    let mut footprint = Footprint::default();
+    let mut map = OrdMap::default();
+    for k in read_only {
+        let key = contract_data_key(*k);
+        let le = LedgerEntry {
+            last_modified_ledger_seq: 0,
+            data: LedgerEntryData::ContractData(ContractDataEntry {
+                contract_id: Hash(CONTRACT_ID),
+                key: ScVal::U32(*k),
+                val: ScVal::U32(*k),
+            }),
+            ext: LedgerEntryExt::V0,
+        };
+        footprint.record_access(&key, AccessType::ReadOnly).unwrap();
+        map.insert(key, Some(le));
+    }
+    for k in read_write {
+        footprint
+            .record_access(&contract_data_key(*k), AccessType::ReadWrite)
+            .unwrap();
+    }
+    let storage = Storage::with_enforcing_footprint_and_map(
+        footprint,
+        MeteredOrdMap {
+            budget: budget.clone(),
+            map,
+        },
+    );
+    Host::with_storage_and_budget(storage, budget).test_ledger_info()
+}
+
+#[test]
+fn modified_keys_track_writes_only() -> Result<(), HostError> {
+    let host = host_with_contract_data(&[1], &[2, 3]);
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        host.get_contract_data(1_u32.into())?;
+        host.put_contract_data(2_u32.into(), 20_u32.into())?;
+        host.put_contract_data(3_u32.into(), 30_u32.into())?;
+        Ok(())
+    })?;
+    assert_eq!(
+        host.get_modified_keys()?,
+        vec![contract_data_key(2), contract_data_key(3)]
+    );
+    // Rewriting an entry with the value it already holds changes nothing.
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        host.put_contract_data(2_u32.into(), 20_u32.into())
+    })?;
+    assert!(host.get_modified_keys()?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn modified_keys_exclude_rolled_back_writes() -> Result<(), HostError> {
+    let host = host_with_contract_data(&[], &[2, 3]);
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        host.put_contract_data(2_u32.into(), 20_u32.into())?;
+        let res: Result<(), HostError> = host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+            host.put_contract_data(3_u32.into(), 30_u32.into())?;
+            Err(host.err_general("fail"))
+        });
+        assert!(res.is_err());
+        Ok(())
+    })?;
+    assert_eq!(host.get_modified_keys()?, vec![contract_data_key(2)]);
+    Ok(())
+}
+
+#[test]
+fn storage_changes_report_net_writes() -> Result<(), HostError> {
+    let host = host_with_contract_data(&[1], &[2, 3, 4]);
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        host.get_contract_data(1_u32.into())?;
+        host.put_contract_data(2_u32.into(), 20_u32.into())?;
+        host.put_contract_data(3_u32.into(), 30_u32.into())?;
+        host.del_contract_data(3_u32.into())?;
+        let res: Result<(), HostError> = host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+            host.put_contract_data(4_u32.into(), 40_u32.into())?;
+            Err(host.err_general("fail"))
+        });
+        assert!(res.is_err());
+        Ok(())
+    })?;
+    let changes = host.storage_changes();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].0, contract_data_key(2));
+    match &changes[0].1 {
+        Some(LedgerEntry {
+            data: LedgerEntryData::ContractData(ContractDataEntry { val, .. }),
+            ..
+        }) => assert_eq!(*val, ScVal::U32(20)),
+        _ => panic!("expected contract data for key 2"),
+    }
+    assert_eq!(changes[1], (contract_data_key(3), None));
+    Ok(())
+}
+
+#[test]
+fn destroy_current_contract_removes_data_and_code() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let id = Hash([1; 32]);
+    let code_key = host.contract_code_ledger_key(id.clone());
+    host.store_contract_code(ScContractCode::Token, id.clone(), &code_key)?;
+    // Data of another contract is left alone.
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        host.put_contract_data(1_u32.into(), 1_u32.into())
+    })?;
+
+    host.with_frame(Frame::Token(id.clone()), || {
+        for k in 1..=3_u32 {
+            host.put_contract_data(k.into(), k.into())?;
+        }
+        let removed = u32::try_from(host.destroy_current_contract()?)?;
+        // Three data entries and the code entry.
+        assert_eq!(removed, 4);
+        assert!(host.get_contract_data(1_u32.into()).is_err());
+        Ok(())
+    })?;
+
+    let id_obj = host.test_bin_obj(&id.0)?.to_object();
+    let args = host.test_vec_obj::<u32>(&[])?;
+    assert!(host
+        .call(id_obj, Symbol::from_str("name"), args.to_object())
+        .is_err());
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        host.get_contract_data(1_u32.into())
+    })?;
+    Ok(())
+}
+
+#[test]
+fn read_only_storage_rejects_writes() -> Result<(), HostError> {
+    let storage = Storage::with_recording_footprint(Rc::new(EmptySnapshot)).into_read_only();
+    let host = Host::with_storage(storage).test_ledger_info();
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        let res = host.put_contract_data(1_u32.into(), 1_u32.into());
+        assert!(HostError::result_matches_err_status(
+            res,
+            ScHostStorageErrorCode::ReadwriteAccessToReadonlyEntry
+        ));
+        let res = host.del_contract_data(1_u32.into());
+        assert!(HostError::result_matches_err_status(
+            res,
+            ScHostStorageErrorCode::ReadwriteAccessToReadonlyEntry
+        ));
+        // Read-only storage puts the whole host in read-only mode.
+        assert!(bool::try_from(host.is_read_only()?)?);
+        let topics = host.test_vec_obj::<u32>(&[])?.to_object();
+        let res = host.contract_event(topics, 1_u32.into());
+        assert!(HostError::result_matches_err_status(
+            res,
+            ScHostStorageErrorCode::ReadwriteAccessToReadonlyEntry
+        ));
+        // Reads are unaffected.
+        assert!(!bool::try_from(host.has_contract_data(1_u32.into())?)?);
+        Ok(())
+    })?;
+    host.set_read_only(false);
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        host.put_contract_data(1_u32.into(), 1_u32.into())
+    })?;
+    Ok(())
+}
+
+#[test]
+fn get_contract_data_keys_lists_only_live_keys_of_current_contract() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    // Data of another contract is not listed.
+    host.with_frame(Frame::Token(Hash([1; 32])), || {
+        host.put_contract_data(9_u32.into(), 9_u32.into())
+    })?;
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        for k in [3_u32, 1, 2] {
+            host.put_contract_data(k.into(), k.into())?;
+        }
+        host.del_contract_data(2_u32.into())?;
+        let keys = host.get_contract_data_keys()?;
+        let expected = host.test_vec_obj::<u32>(&[1, 3])?;
+        assert_eq!(host.obj_cmp(keys.into(), expected.to_raw())?, 0);
+        Ok(())
+    })
+}
+
+#[test]
+fn try_get_contract_data_returns_void_when_absent() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        assert!(host.try_get_contract_data(1_u32.into())?.is_void());
+        host.put_contract_data(1_u32.into(), 7_u32.into())?;
+        assert_eq!(u32::try_from(host.try_get_contract_data(1_u32.into())?)?, 7);
+        host.del_contract_data(1_u32.into())?;
+        assert!(host.try_get_contract_data(1_u32.into())?.is_void());
+        Ok(())
+    })?;
+
+    // Faults other than absence still trap.
+    let host = host_with_contract_data(&[], &[]);
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        let res = host.try_get_contract_data(1_u32.into());
+        assert!(HostError::result_matches_err_status(
+            res,
+            ScHostStorageErrorCode::AccessToUnknownEntry
+        ));
+        Ok(())
+    })
+}
+
+#[test]
+fn typed_contract_data_accessors() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        host.put_contract_data_from(1_u32.into(), 7_u32)?;
+        assert_eq!(host.get_contract_data_as::<u32>(1_u32.into())?, 7);
+        host.put_contract_data_from(2_u32.into(), true)?;
+        let res = host.get_contract_data_as::<u32>(2_u32.into());
+        assert!(HostError::result_matches_err_status(
+            res,
+            ScHostValErrorCode::UnexpectedValType
+        ));
+        Ok(())
+    })
+}
+
+#[test]
+fn read_only_host_rejects_writes_and_events() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        assert!(!bool::try_from(host.is_read_only()?)?);
+        host.put_contract_data(1_u32.into(), 1_u32.into())?;
+        host.contract_event(host.test_vec_obj::<u32>(&[])?.to_object(), 1_u32.into())?;
+        Ok(())
+    })?;
+
+    host.set_read_only(true);
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        assert!(bool::try_from(host.is_read_only()?)?);
+        let code = ScHostStorageErrorCode::ReadwriteAccessToReadonlyEntry;
+        let res = host.put_contract_data(2_u32.into(), 2_u32.into());
+        assert!(HostError::result_matches_err_status(res, code));
+        let res = host.del_contract_data(1_u32.into());
+        assert!(HostError::result_matches_err_status(res, code));
+        let topics = host.test_vec_obj::<u32>(&[])?.to_object();
+        let res = host.contract_event(topics, 1_u32.into());
+        assert!(HostError::result_matches_err_status(res, code));
+        // Reads still work.
+        assert_eq!(u32::try_from(host.get_contract_data(1_u32.into())?)?, 1);
+        Ok(())
+    })
+}
+
+#[test]
+fn is_token_contract_checks_code_kind() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let token_id = Hash([1; 32]);
+    let token_key = host.contract_code_ledger_key(token_id.clone());
+    host.store_contract_code(ScContractCode::Token, token_id.clone(), &token_key)?;
+    let wasm_id = Hash([2; 32]);
+    let wasm_key = host.contract_code_ledger_key(wasm_id.clone());
+    let wasm = ScContractCode::Wasm(vec![0, 1, 2].try_into().unwrap());
+    host.store_contract_code(wasm, wasm_id.clone(), &wasm_key)?;
+
+    let token_obj = host.test_bin_obj(&token_id.0)?.to_object();
+    assert!(bool::try_from(host.is_token_contract(token_obj)?)?);
+    let wasm_obj = host.test_bin_obj(&wasm_id.0)?.to_object();
+    assert!(!bool::try_from(host.is_token_contract(wasm_obj)?)?);
+    let missing_obj = host.test_bin_obj(&[3; 32])?.to_object();
+    assert!(host.is_token_contract(missing_obj).is_err());
+    Ok(())
+}
+
+#[test]
+fn contract_code_presence_and_hash() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let token_id = Hash([1; 32]);
+    let token_key = host.contract_code_ledger_key(token_id.clone());
+    host.store_contract_code(ScContractCode::Token, token_id.clone(), &token_key)?;
+    let wasm_id = Hash([2; 32]);
+    let wasm_key = host.contract_code_ledger_key(wasm_id.clone());
+    let wasm = ScContractCode::Wasm(vec![0, 1, 2].try_into().unwrap());
+    host.store_contract_code(wasm, wasm_id.clone(), &wasm_key)?;
+
+    let token_obj = host.test_bin_obj(&token_id.0)?.to_object();
+    let wasm_obj = host.test_bin_obj(&wasm_id.0)?.to_object();
+    let missing_obj = host.test_bin_obj(&[3; 32])?.to_object();
+    assert!(bool::try_from(host.has_contract_code(token_obj)?)?);
+    assert!(bool::try_from(host.has_contract_code(wasm_obj)?)?);
+    assert!(!bool::try_from(host.has_contract_code(missing_obj)?)?);
+
+    let hash = host.get_contract_code_hash(wasm_obj)?;
+    let expected = host.compute_hash_sha256(host.test_bin_obj(&[0, 1, 2])?.to_object())?;
+    assert_eq!(host.obj_cmp(hash.to_raw(), expected.to_raw())?, 0);
+    let res = host.get_contract_code_hash(token_obj);
+    let code = ScHostFnErrorCode::InputArgsInvalid;
+    assert!(HostError::result_matches_err_status(res, code));
+    assert!(host.get_contract_code_hash(missing_obj).is_err());
+    Ok(())
+}
+
+#[test]
+fn diff_storage_reports_written_keys() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let snapshot = || host.visit_storage(|storage| Ok(storage.clone()));
+    let before = snapshot()?;
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        host.put_contract_data(1_u32.into(), 1_u32.into())
+    })?;
+    assert_eq!(
+        host.diff_storage(&before)?,
+        vec![(contract_data_key(1), StorageDiffKind::Added)]
+    );
+
+    let before = snapshot()?;
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        host.put_contract_data(1_u32.into(), 2_u32.into())?;
+        host.put_contract_data(2_u32.into(), 2_u32.into())
+    })?;
+    assert_eq!(
+        host.diff_storage(&before)?,
+        vec![
+            (contract_data_key(1), StorageDiffKind::Changed),
+            (contract_data_key(2), StorageDiffKind::Added),
+        ]
+    );
+    assert_eq!(host.diff_storage(&snapshot()?)?, vec![]);
+    Ok(())
+}
+
+#[test]
+fn validate_scval_rejects_malformed_values() -> Result<(), HostError> {
+    let host = Host::default();
+    let entry = |k: u32| ScMapEntry {
+        key: ScVal::U32(k),
+        val: ScVal::Symbol("ok".as_bytes().try_into().unwrap()),
+    };
+    let map = |entries: Vec<ScMapEntry>| {
+        ScVal::Object(Some(ScObject::Map(ScMap(entries.try_into().unwrap()))))
+    };
+    let bytes = |len: usize| ScVal::Object(Some(ScObject::Bytes(vec![0; len].try_into().unwrap())));
+    host.validate_scval(&map(vec![entry(1), entry(2)]))?;
+    host.validate_scval(&bytes(MAX_SCVAL_BINARY_LEN))?;
+
+    let bad_symbol = ScVal::Symbol("no-dash".as_bytes().try_into().unwrap());
+    // Nested values are checked too.
+    let nested = ScVal::Object(Some(ScObject::Vec(ScVec(
+        vec![bad_symbol].try_into().unwrap(),
+    ))));
+    let res = host.validate_scval(&nested);
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostValErrorCode::SymbolBadChar
+    ));
+    for unsorted in [vec![entry(2), entry(1)], vec![entry(1), entry(1)]] {
+        let res = host.validate_scval(&map(unsorted));
+        assert!(HostError::result_matches_err_status(
+            res,
+            ScHostFnErrorCode::InputArgsInvalid
+        ));
+    }
+    let res = host.validate_scval(&bytes(MAX_SCVAL_BINARY_LEN + 1));
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostFnErrorCode::InputArgsWrongLength
+    ));
+    Ok(())
+}
+
+#[test]
+fn put_contract_data_rejects_oversized_binary() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let big = host.test_bin_obj(&vec![0; MAX_SCVAL_BINARY_LEN + 1])?;
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        let res = host.put_contract_data(1_u32.into(), big.to_raw());
+        assert!(HostError::result_matches_err_status(
+            res,
+            ScHostFnErrorCode::InputArgsWrongLength
+        ));
+        Ok(())
+    })
+}
+
+#[test]
+fn put_contract_data_accepts_host_ordered_map() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    // The host orders BigInt 2 before 256, unlike the derived order of their
+    // XDR, so a map it built must still pass validation when stored.
+    let map = host.map_new()?;
+    let map = host.map_put(map, host.bigint_from_u64(256)?.to_raw(), 1_u32.into())?;
+    let map = host.map_put(map, host.bigint_from_u64(2)?.to_raw(), 2_u32.into())?;
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        host.put_contract_data(1_u32.into(), map.to_raw())
+    })?;
+    Ok(())
+}
+
+#[test]
+fn create_contract_with_id_returns_id() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let id = Hash([1; 32]);
+    let id_obj = host.test_bin_obj(&id.0)?.to_object();
+    let res = host.create_contract_with_id(ScContractCode::Token, id_obj)?;
+    assert_eq!(host.hash_from_obj_input("res", res)?, id);
+    let code_key = host.contract_code_ledger_key(id);
+    assert!(host.visit_storage(|storage| storage.has(&code_key))?);
+    // Creating it again fails.
+    let res = host.create_contract_with_id(ScContractCode::Token, id_obj);
+    assert!(HostError::result_matches_err_status(
+        res,
+        CONTRACT_ALREADY_EXISTS
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "testutils")]
+#[test]
+fn put_ledger_entries_seeds_enforcing_storage() -> Result<(), HostError> {
+    let host = Host::with_storage(Storage::default()).test_ledger_info();
+    let cpu = host.get_budget(|budget| budget.get_cpu_insns_count());
+    host.put_ledger_entries((1..=2).map(|k| {
+        let le = LedgerEntry {
+            last_modified_ledger_seq: 0,
+            data: LedgerEntryData::ContractData(ContractDataEntry {
+                contract_id: Hash(CONTRACT_ID),
+                key: ScVal::U32(k),
+                val: ScVal::U32(k * 10),
+            }),
+            ext: LedgerEntryExt::V0,
+        };
+        (contract_data_key(k), le)
+    }));
+    host.get_budget(|budget| assert_eq!(budget.get_cpu_insns_count(), cpu));
+    host.with_frame(Frame::Token(Hash(CONTRACT_ID)), || {
+        assert_eq!(u32::try_from(host.get_contract_data(2_u32.into())?)?, 20);
+        // Seeded keys are writable too.
+        host.put_contract_data(1_u32.into(), 11_u32.into())?;
+        assert_eq!(u32::try_from(host.get_contract_data(1_u32.into())?)?, 11);
+        Ok(())
+    })
+}
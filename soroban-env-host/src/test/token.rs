@@ -0,0 +1,319 @@
+use crate::{
+    host::Frame,
+    native_contract::{
+        base_types::{self, BigInt, BytesN},
+        token::{
+            admin::write_administrator,
+            balance::{
+                compute_fee, read_balance, read_balances, read_holders, receive_balance,
+                transfer_balance, write_state,
+            },
+            metadata::write_fee_bps,
+            public_types::{Authorization, Identifier},
+        },
+    },
+    xdr::{
+        Hash, HashIdPreimage, HashIdPreimageEd25519ContractId, ScContractCode, ScHostFnErrorCode,
+        Uint256, WriteXdr,
+    },
+    CheckedEnv, Host, HostError, Object, RawVal, Symbol, TryFromVal, TryIntoVal,
+};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use sha2::{Digest, Sha256};
+
+fn with_token_frame<F, U>(host: &Host, f: F) -> Result<U, HostError>
+where
+    F: FnOnce() -> Result<U, HostError>,
+{
+    host.with_frame(Frame::Token(Hash([0; 32])), f)
+}
+
+fn bigint_to_u64(host: &Host, bi: BigInt) -> Result<u64, HostError> {
+    host.bigint_to_u64(Object::from(bi))
+}
+
+fn test_identifier(host: &Host, id: u8) -> Result<Identifier, HostError> {
+    let bin = host.test_bin_obj(&[id; 32])?.to_object();
+    Ok(Identifier::Ed25519(BytesN::try_from_val(host, bin)?))
+}
+
+#[test]
+fn compute_fee_in_basis_points() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    with_token_frame(&host, || {
+        write_fee_bps(&host, 30)?;
+        let fee = compute_fee(&host, BigInt::from_u64(&host, 10_000)?)?;
+        assert_eq!(bigint_to_u64(&host, fee)?, 30);
+        // 12345 * 30 / 10000 = 37.035, which rounds down.
+        let fee = compute_fee(&host, BigInt::from_u64(&host, 12_345)?)?;
+        assert_eq!(bigint_to_u64(&host, fee)?, 37);
+        // Amounts too small to owe a whole unit pay nothing.
+        let fee = compute_fee(&host, BigInt::from_u64(&host, 333)?)?;
+        assert_eq!(bigint_to_u64(&host, fee)?, 0);
+
+        // The fee withheld from the recipient goes to the administrator.
+        let from = test_identifier(&host, 1)?;
+        let to = test_identifier(&host, 2)?;
+        let admin = test_identifier(&host, 3)?;
+        write_administrator(&host, admin.clone())?;
+        receive_balance(&host, from.clone(), BigInt::from_u64(&host, 10_000)?)?;
+        transfer_balance(
+            &host,
+            from.clone(),
+            to.clone(),
+            BigInt::from_u64(&host, 10_000)?,
+        )?;
+        assert_eq!(bigint_to_u64(&host, read_balance(&host, from)?)?, 0);
+        assert_eq!(bigint_to_u64(&host, read_balance(&host, to)?)?, 9_970);
+        assert_eq!(bigint_to_u64(&host, read_balance(&host, admin)?)?, 30);
+        Ok(())
+    })
+}
+
+#[test]
+fn zero_fee_is_a_no_op() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    with_token_frame(&host, || {
+        let fee = compute_fee(&host, BigInt::from_u64(&host, 12_345)?)?;
+        assert_eq!(bigint_to_u64(&host, fee)?, 0);
+
+        let from = test_identifier(&host, 1)?;
+        let to = test_identifier(&host, 2)?;
+        receive_balance(&host, from.clone(), BigInt::from_u64(&host, 12_345)?)?;
+        transfer_balance(&host, from, to.clone(), BigInt::from_u64(&host, 12_345)?)?;
+        assert_eq!(bigint_to_u64(&host, read_balance(&host, to)?)?, 12_345);
+        Ok(())
+    })
+}
+
+#[test]
+fn fee_above_one_hundred_percent_is_rejected() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    with_token_frame(&host, || {
+        assert!(write_fee_bps(&host, 10_001).is_err());
+        Ok(())
+    })
+}
+
+#[test]
+fn set_fee_requires_admin() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let id = Hash([0; 32]);
+    let code_key = host.contract_code_ledger_key(id.clone());
+    host.store_contract_code(ScContractCode::Token, id.clone(), &code_key)?;
+    let admin_id = Hash([9; 32]);
+    let admin_bin = host.test_bin_obj(&admin_id.0)?.to_object();
+    let admin = Identifier::Contract(BytesN::try_from_val(&host, admin_bin)?);
+    with_token_frame(&host, || write_administrator(&host, admin))?;
+
+    let id_obj = host.test_bin_obj(&id.0)?.to_object();
+    let auth: RawVal = Authorization::Contract.try_into_val(&host)?;
+    let set_fee = Symbol::from_str("set_fee");
+    let args = [auth, 25_u32.into()];
+    // Another contract cannot set the fee.
+    let res = host.with_frame(Frame::Token(Hash([8; 32])), || {
+        host.call_token(id_obj, set_fee, &args)
+    });
+    assert!(res.is_err());
+    host.with_frame(Frame::Token(admin_id), || {
+        host.call_token(id_obj, set_fee, &args)
+    })?;
+    let fee = host.call_token(id_obj, Symbol::from_str("fee_bps"), &[])?;
+    assert_eq!(u32::try_from(fee)?, 25);
+    Ok(())
+}
+
+#[test]
+fn read_balances_in_order_with_missing_as_zero() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    with_token_frame(&host, || {
+        let a = test_identifier(&host, 1)?;
+        let b = test_identifier(&host, 2)?;
+        let c = test_identifier(&host, 3)?;
+        receive_balance(&host, a.clone(), BigInt::from_u64(&host, 100)?)?;
+        receive_balance(&host, b.clone(), BigInt::from_u64(&host, 200)?)?;
+
+        let mut ids = base_types::Vec::new(&host)?;
+        for id in [a, b, c] {
+            ids.push(id)?;
+        }
+        let balances = read_balances(&host, ids)?;
+        assert_eq!(balances.len()?, 3);
+        let balances = (0..3)
+            .map(|i| bigint_to_u64(&host, balances.get::<BigInt>(i)?))
+            .collect::<Result<Vec<u64>, HostError>>()?;
+        assert_eq!(balances, vec![100, 200, 0]);
+        Ok(())
+    })
+}
+
+#[test]
+fn transfer_balance_moves_funds() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    with_token_frame(&host, || {
+        let from = test_identifier(&host, 1)?;
+        let to = test_identifier(&host, 2)?;
+        receive_balance(&host, from.clone(), BigInt::from_u64(&host, 100)?)?;
+        receive_balance(&host, to.clone(), BigInt::from_u64(&host, 5)?)?;
+        transfer_balance(
+            &host,
+            from.clone(),
+            to.clone(),
+            BigInt::from_u64(&host, 40)?,
+        )?;
+        assert_eq!(
+            bigint_to_u64(&host, read_balance(&host, from.clone())?)?,
+            60
+        );
+        assert_eq!(bigint_to_u64(&host, read_balance(&host, to)?)?, 45);
+
+        // A self-transfer leaves the balance as it was.
+        transfer_balance(
+            &host,
+            from.clone(),
+            from.clone(),
+            BigInt::from_u64(&host, 60)?,
+        )?;
+        assert_eq!(bigint_to_u64(&host, read_balance(&host, from)?)?, 60);
+        Ok(())
+    })
+}
+
+#[test]
+fn transfer_balance_insufficient_funds() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    with_token_frame(&host, || {
+        let from = test_identifier(&host, 1)?;
+        let to = test_identifier(&host, 2)?;
+        receive_balance(&host, from.clone(), BigInt::from_u64(&host, 10)?)?;
+        let res = transfer_balance(
+            &host,
+            from.clone(),
+            to.clone(),
+            BigInt::from_u64(&host, 11)?,
+        );
+        assert!(res.is_err());
+        assert_eq!(bigint_to_u64(&host, read_balance(&host, from)?)?, 10);
+        assert_eq!(bigint_to_u64(&host, read_balance(&host, to)?)?, 0);
+        Ok(())
+    })
+}
+
+#[test]
+fn transfer_balance_frozen_sender() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    with_token_frame(&host, || {
+        let from = test_identifier(&host, 1)?;
+        let to = test_identifier(&host, 2)?;
+        receive_balance(&host, from.clone(), BigInt::from_u64(&host, 10)?)?;
+        write_state(&host, from.clone(), true)?;
+        let res = transfer_balance(&host, from.clone(), to.clone(), BigInt::from_u64(&host, 1)?);
+        assert!(res.is_err());
+        assert_eq!(bigint_to_u64(&host, read_balance(&host, from)?)?, 10);
+        assert_eq!(bigint_to_u64(&host, read_balance(&host, to)?)?, 0);
+        Ok(())
+    })
+}
+
+#[test]
+fn call_token_matches_generic_call() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let id = Hash([0; 32]);
+    let code_key = host.contract_code_ledger_key(id.clone());
+    host.store_contract_code(ScContractCode::Token, id.clone(), &code_key)?;
+    let holder = test_identifier(&host, 1)?;
+    with_token_frame(&host, || {
+        receive_balance(&host, holder.clone(), BigInt::from_u64(&host, 42)?)
+    })?;
+
+    let id_obj = host.test_bin_obj(&id.0)?.to_object();
+    let holder: RawVal = holder.try_into_val(&host)?;
+    let func = Symbol::from_str("balance");
+    let direct = host.call_token(id_obj, func, &[holder])?;
+    let args = host.vec_push(host.vec_new(().into())?, holder)?;
+    let generic = host.call(id_obj, func, args)?;
+    assert_eq!(
+        bigint_to_u64(&host, BigInt::try_from_val(&host, direct)?)?,
+        42
+    );
+    assert_eq!(
+        bigint_to_u64(&host, BigInt::try_from_val(&host, generic)?)?,
+        42
+    );
+    Ok(())
+}
+
+#[test]
+fn call_token_rejects_non_token_contract() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let id = Hash([0; 32]);
+    let code_key = host.contract_code_ledger_key(id.clone());
+    let wasm = ScContractCode::Wasm(vec![0; 4].try_into().unwrap());
+    host.store_contract_code(wasm, id.clone(), &code_key)?;
+    let id_obj = host.test_bin_obj(&id.0)?.to_object();
+    let res = host.call_token(id_obj, Symbol::from_str("fee_bps"), &[]);
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostFnErrorCode::InputArgsInvalid
+    ));
+    Ok(())
+}
+
+#[test]
+fn read_holders_skips_zero_balances() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    with_token_frame(&host, || {
+        let a = test_identifier(&host, 1)?;
+        let b = test_identifier(&host, 2)?;
+        let c = test_identifier(&host, 3)?;
+        receive_balance(&host, a.clone(), BigInt::from_u64(&host, 100)?)?;
+        receive_balance(&host, b.clone(), BigInt::from_u64(&host, 0)?)?;
+        receive_balance(&host, c.clone(), BigInt::from_u64(&host, 300)?)?;
+
+        // Holders come back in key order.
+        let holders = read_holders(&host)?;
+        assert_eq!(holders.len()?, 2);
+        for (i, (id, amount)) in [(a, 100), (c, 300)].into_iter().enumerate() {
+            let pair = Object::from(holders.get::<base_types::Vec>(i as u32)?);
+            let holder = host.vec_get(pair, 0_u32.into())?;
+            let expected: RawVal = id.try_into_val(&host)?;
+            assert_eq!(host.obj_cmp(holder, expected)?, 0);
+            let balance = BigInt::try_from_val(&host, host.vec_get(pair, 1_u32.into())?)?;
+            assert_eq!(bigint_to_u64(&host, balance)?, amount);
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn create_token_from_ed25519_checks_signature() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let secret = SecretKey::from_bytes(&[1; 32]).unwrap();
+    let public: PublicKey = (&secret).into();
+    let keypair = Keypair { secret, public };
+    let salt = [7; 32];
+    let separator = "create_token_from_ed25519(salt: u256, key: u256, sig: Vec<u8>)";
+    let sign = |preimage: &[u8]| -> Result<Object, HostError> {
+        let sig = keypair.sign(Sha256::digest(preimage).as_slice());
+        Ok(host.test_bin_obj(&sig.to_bytes())?.to_object())
+    };
+    let salt_obj = host.test_bin_obj(&salt)?.to_object();
+    let key_obj = host.test_bin_obj(public.as_bytes())?.to_object();
+
+    let bad_sig = sign(&salt)?;
+    assert!(host
+        .create_token_from_ed25519(salt_obj, key_obj, bad_sig)
+        .is_err());
+
+    let sig = sign(&[separator.as_bytes(), &salt].concat())?;
+    let id = host.create_token_from_ed25519(salt_obj, key_obj, sig)?;
+    let pre_image = HashIdPreimage::ContractIdFromEd25519(HashIdPreimageEd25519ContractId {
+        ed25519: Uint256(public.to_bytes()),
+        salt: Uint256(salt),
+    });
+    let mut buf = std::vec::Vec::new();
+    pre_image.write_xdr(&mut buf).unwrap();
+    let expected = host.test_bin_obj(Sha256::digest(&buf).as_slice())?;
+    assert_eq!(host.obj_cmp(id.into(), expected.into())?, 0);
+    Ok(())
+}
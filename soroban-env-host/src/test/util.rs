@@ -1,15 +1,17 @@
 use crate::{
     budget::{Budget, CostType},
-    host::metered_map::MeteredOrdMap,
+    host::{metered_map::MeteredOrdMap, LedgerInfo},
     host_object::{HostObj, HostVal},
     im_rc::OrdMap,
-    storage::{AccessType, Footprint, Storage},
+    storage::{AccessType, Footprint, SnapshotSource, Storage},
     xdr::{
         ContractDataEntry, Hash, LedgerEntry, LedgerEntryData, LedgerEntryExt, LedgerKey,
-        LedgerKeyContractData, ScContractCode, ScObject, ScStatic, ScVal, ScVec,
+        LedgerKeyContractData, ScContractCode, ScHostStorageErrorCode, ScObject, ScStatic, ScVal,
+        ScVec,
     },
     Host, HostError,
 };
+use std::rc::Rc;
 
 // Test utilities for the host, used in various tests in sub-modules.
 pub(crate) trait AsScVal {
@@ -28,12 +30,51 @@ impl AsScVal for i32 {
     }
 }
 
+impl AsScVal for u64 {
+    fn as_scval(&self) -> ScVal {
+        ScVal::Object(Some(ScObject::U64(*self)))
+    }
+}
+
+// A ledger snapshot with no entries, for tests that run in recording mode so
+// they can read and write arbitrary keys without declaring a footprint.
+pub(crate) struct EmptySnapshot;
+
+impl SnapshotSource for EmptySnapshot {
+    fn get(&self, _key: &LedgerKey) -> Result<LedgerEntry, HostError> {
+        Err(ScHostStorageErrorCode::MissingKeyInGet.into())
+    }
+
+    fn has(&self, _key: &LedgerKey) -> Result<bool, HostError> {
+        Ok(false)
+    }
+}
+
 #[allow(dead_code)]
 impl Host {
     pub(crate) fn test_host() -> Self {
         Host::default()
     }
 
+    pub(crate) fn test_host_with_recording_footprint() -> Self {
+        let storage = Storage::with_recording_footprint(Rc::new(EmptySnapshot));
+        Host::with_storage(storage).test_ledger_info()
+    }
+
+    // Sets a ledger info, as writing contract data requires one.
+    pub(crate) fn test_ledger_info(self) -> Self {
+        self.set_ledger_info(LedgerInfo {
+            protocol_version: 1,
+            sequence_number: 1,
+            timestamp: 0,
+            network_id: vec![0; 32],
+            base_reserve: 0,
+            base_fee: 0,
+        })
+        .unwrap();
+        self
+    }
+
     pub(crate) fn test_budget(self) -> Self {
         self.get_budget(|budget| {
             budget.reset_limits(100_000, 100_000); // something big but finite that we may exceed
@@ -175,6 +175,26 @@ fn vec_slice_and_cmp() -> Result<(), HostError> {
     Ok(())
 }
 
+#[test]
+fn obj_eq_agrees_with_obj_cmp() -> Result<(), HostError> {
+    let host = Host::default();
+    let vals: Vec<RawVal> = vec![
+        host.test_vec_obj::<u32>(&[1, 2, 3])?.into(),
+        host.test_vec_obj::<u32>(&[1, 2, 3])?.into(),
+        host.test_vec_obj::<u32>(&[1, 2])?.into(),
+        host.test_vec_obj::<u32>(&[1, 2, 4])?.into(),
+        host.bigint_from_u64(7)?.into(),
+        host.bigint_from_u64(7)?.into(),
+    ];
+    for a in vals.iter() {
+        for b in vals.iter() {
+            let eq = bool::try_from(host.obj_eq(*a, *b)?)?;
+            assert_eq!(eq, host.obj_cmp(*a, *b)? == 0);
+        }
+    }
+    Ok(())
+}
+
 #[test]
 fn vec_slice_start_equal_to_end() -> Result<(), HostError> {
     let host = Host::default();
@@ -290,3 +310,102 @@ fn vec_append_empty() -> Result<(), HostError> {
     assert_eq!(host.obj_cmp(obj0.into(), obj1.into())?, 0);
     Ok(())
 }
+
+#[test]
+fn vec_is_contiguous_u64() -> Result<(), HostError> {
+    let host = Host::default();
+    let cases: [(&[u64], bool); 5] = [
+        (&[1, 2, 3], true),
+        (&[1, 3], false),
+        (&[1, 1], false),
+        (&[], true),
+        (&[u64::MAX], true),
+    ];
+    for (vals, expected) in cases {
+        let obj = host.test_vec_obj::<u64>(vals)?;
+        let res = host.vec_is_contiguous_u64(obj.to_object())?;
+        assert_eq!(bool::try_from(res)?, expected);
+    }
+    let obj = host.test_vec_obj::<u32>(&[1, 2])?;
+    let res = host.vec_is_contiguous_u64(obj.to_object());
+    let code = ScHostObjErrorCode::UnexpectedType;
+    assert!(HostError::result_matches_err_status(res, code));
+    Ok(())
+}
+
+#[test]
+fn vec_obj_diff() -> Result<(), HostError> {
+    let host = Host::default();
+    let old = host.test_vec_obj::<u32>(&[1, 2, 3])?.to_object();
+    let new = host.test_vec_obj::<u32>(&[1, 5])?.to_object();
+
+    let diff = host.obj_diff(old.into(), new.into())?;
+    let added: Object = host.vec_get(diff, 0_u32.into())?.try_into()?;
+    let removed: Object = host.vec_get(diff, 1_u32.into())?.try_into()?;
+    let changed: Object = host.vec_get(diff, 2_u32.into())?.try_into()?;
+
+    assert_eq!(u32::try_from(host.map_len(added)?)?, 0);
+    // Index 2 was truncated and index 1 overwritten.
+    assert_eq!(u32::try_from(host.vec_len(removed)?)?, 1);
+    assert_eq!(u32::try_from(host.vec_get(removed, 0_u32.into())?)?, 2);
+    assert_eq!(u32::try_from(host.map_len(changed)?)?, 1);
+    assert_eq!(u32::try_from(host.map_get(changed, 1_u32.into())?)?, 5);
+    Ok(())
+}
+
+#[test]
+fn vec_obj_apply_diff_round_trip() -> Result<(), HostError> {
+    let host = Host::default();
+    let pairs: [(&[u32], &[u32]); 4] = [
+        (&[1, 2, 3], &[1, 5]),
+        (&[1, 2], &[1, 2, 3, 4]),
+        (&[], &[7]),
+        (&[4, 5, 6], &[6, 5, 4]),
+    ];
+    for (old, new) in pairs {
+        let old = host.test_vec_obj::<u32>(old)?.to_object();
+        let new = host.test_vec_obj::<u32>(new)?.to_object();
+        let diff = host.obj_diff(old.into(), new.into())?;
+        let applied = host.obj_apply_diff(old.into(), diff)?;
+        assert_eq!(host.obj_cmp(applied, new.into())?, 0);
+    }
+    Ok(())
+}
+
+#[test]
+fn vec_reverse_and_sort() -> Result<(), HostError> {
+    let host = Host::default();
+    let obj = host.test_vec_obj::<u32>(&[3, 1, 2, 1])?;
+    let reversed = host.vec_reverse(obj.to_object())?;
+    let want = host.test_vec_obj::<u32>(&[1, 2, 1, 3])?;
+    assert_eq!(host.obj_cmp(reversed.into(), want.into())?, 0);
+
+    let sorted = host.vec_sort(obj.to_object())?;
+    let want = host.test_vec_obj::<u32>(&[1, 1, 2, 3])?;
+    assert_eq!(host.obj_cmp(sorted.into(), want.into())?, 0);
+
+    // The source vector is untouched.
+    let orig = host.test_vec_obj::<u32>(&[3, 1, 2, 1])?;
+    assert_eq!(host.obj_cmp(obj.into(), orig.into())?, 0);
+    Ok(())
+}
+
+#[test]
+fn vec_dedup_removes_adjacent_duplicates() -> Result<(), HostError> {
+    let host = Host::default();
+    let cases: [(&[u32], &[u32]); 5] = [
+        (&[], &[]),
+        (&[5, 5, 5], &[5]),
+        (&[1, 2, 3], &[1, 2, 3]),
+        (&[1, 1, 2, 3, 3], &[1, 2, 3]),
+        // Only adjacent duplicates go.
+        (&[1, 2, 1], &[1, 2, 1]),
+    ];
+    for (input, expected) in cases {
+        let obj = host.test_vec_obj(input)?;
+        let deduped = host.vec_dedup(obj.to_object())?;
+        let want = host.test_vec_obj(expected)?;
+        assert_eq!(host.obj_cmp(deduped.into(), want.into())?, 0);
+    }
+    Ok(())
+}
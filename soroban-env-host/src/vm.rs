@@ -16,7 +16,7 @@ use std::{io::Cursor, ops::RangeInclusive, rc::Rc};
 
 use super::{
     xdr::{Hash, ScVal, ScVec},
-    Host, RawVal,
+    Host, RawVal, Symbol,
 };
 use func_info::HOST_FUNCTIONS;
 use parity_wasm::elements::{self, Internal, Type};
@@ -274,7 +274,8 @@ impl Vm {
         args: &[RawVal],
     ) -> Result<RawVal, HostError> {
         host.charge_budget(CostType::VmInvokeFunction, args.len() as u64)?;
-        host.with_frame(Frame::ContractVM(self.clone()), || {
+        let sym = Symbol::try_from_str(func).ok();
+        host.with_call_frame(Frame::ContractVM(self.clone()), sym, args, || {
             let wasm_args: Vec<_> = args
                 .iter()
                 .map(|i| RuntimeValue::I64(i.get_payload() as i64))